@@ -0,0 +1,138 @@
+//! Limit on the number of retired values waiting for reclamation.
+//!
+//! A reader that holds a guard for a long time keeps every value retired after it pinned the
+//! lock alive. The limit turns that unbounded growth into backpressure on writers.
+//!
+//! 等待回收的退休值数量上限。
+//!
+//! 长时间持有守卫的读者会使其固定锁之后退休的所有值保持存活。该上限将这种无界增长转化为对写者的背压。
+
+use core::fmt;
+
+#[cfg(not(feature = "std"))]
+use alloc::boxed::Box;
+
+use crate::sync::Arc;
+
+/// What a commit does when the backlog of retired values is full.
+///
+/// Set with [`LfrLockBuilder::max_backlog`](crate::LfrLockBuilder::max_backlog).
+///
+/// 当退休值积压已满时提交的行为。
+///
+/// 通过 [`LfrLockBuilder::max_backlog`](crate::LfrLockBuilder::max_backlog) 设置。
+#[derive(Clone)]
+pub enum BacklogPolicy {
+    /// Wait until readers have moved on and the backlog is below the limit again.
+    ///
+    /// The writer Mutex stays held while waiting. A thread that commits while holding a read
+    /// guard of the same lock may wait forever.
+    ///
+    /// 等待直到读者离开，积压重新低于上限。
+    ///
+    /// 等待期间一直持有写入 Mutex。在持有同一个锁的读取守卫时提交的线程可能会永远等待。
+    Block,
+    /// Make [`LfrLock::try_store`](crate::LfrLock::try_store) fail with
+    /// [`TryStoreError::BacklogFull`](crate::TryStoreError::BacklogFull).
+    ///
+    /// Commit paths that cannot fail commit anyway.
+    ///
+    /// 使 [`LfrLock::try_store`](crate::LfrLock::try_store) 以
+    /// [`TryStoreError::BacklogFull`](crate::TryStoreError::BacklogFull) 失败。
+    ///
+    /// 不会失败的提交路径照常提交。
+    Reject,
+    /// Call the callback with the current backlog, then commit anyway.
+    ///
+    /// The callback runs while the writer Mutex is held and must not write to the same lock.
+    /// With the `portable-atomic` feature, the `Arc` is `portable_atomic_util::Arc`, so prefer
+    /// [`callback`](Self::callback) to build it.
+    ///
+    /// 以当前积压数量调用回调，然后照常提交。
+    ///
+    /// 回调在持有写入 Mutex 时运行，不得写入同一个锁。
+    /// 启用 `portable-atomic` feature 时，`Arc` 是 `portable_atomic_util::Arc`，因此应优先使用
+    /// [`callback`](Self::callback) 构建它。
+    Callback(Arc<dyn Fn(usize) + Send + Sync>),
+}
+
+impl BacklogPolicy {
+    /// Create a [`Callback`](Self::Callback) policy from a closure.
+    ///
+    /// 从闭包创建 [`Callback`](Self::Callback) 策略。
+    #[inline]
+    pub fn callback<F>(f: F) -> Self
+    where
+        F: Fn(usize) + Send + Sync + 'static,
+    {
+        let f: Box<dyn Fn(usize) + Send + Sync> = Box::new(f);
+        BacklogPolicy::Callback(Arc::from(f))
+    }
+}
+
+impl fmt::Debug for BacklogPolicy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BacklogPolicy::Block => f.write_str("Block"),
+            BacklogPolicy::Reject => f.write_str("Reject"),
+            BacklogPolicy::Callback(_) => f.debug_tuple("Callback").finish_non_exhaustive(),
+        }
+    }
+}
+
+/// Error of [`LfrLock::try_store`](crate::LfrLock::try_store) when the backlog is full, carried
+/// by [`TryStoreError::BacklogFull`](crate::TryStoreError::BacklogFull).
+///
+/// Carries the rejected value back to the caller.
+///
+/// 积压已满时 [`LfrLock::try_store`](crate::LfrLock::try_store) 的错误，由
+/// [`TryStoreError::BacklogFull`](crate::TryStoreError::BacklogFull) 携带。
+///
+/// 将被拒绝的值交还给调用者。
+pub struct BacklogFull<T> {
+    value: T,
+    backlog: usize,
+}
+
+impl<T> BacklogFull<T> {
+    #[inline]
+    pub(crate) fn new(value: T, backlog: usize) -> Self {
+        BacklogFull { value, backlog }
+    }
+
+    /// Consume the error and return the rejected value.
+    ///
+    /// 消耗该错误并返回被拒绝的值。
+    #[inline]
+    pub fn into_inner(self) -> T {
+        self.value
+    }
+
+    /// Get the number of retired values that were waiting when the value was rejected.
+    ///
+    /// 获取值被拒绝时正在等待的退休值数量。
+    #[inline]
+    pub fn backlog(&self) -> usize {
+        self.backlog
+    }
+}
+
+impl<T> fmt::Debug for BacklogFull<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("BacklogFull")
+            .field("backlog", &self.backlog)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<T> fmt::Display for BacklogFull<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} retired values are waiting for reclamation",
+            self.backlog
+        )
+    }
+}
+
+impl<T> core::error::Error for BacklogFull<T> {}
@@ -0,0 +1,326 @@
+//! Builder for configuring a new lock.
+//!
+//! 用于配置新锁的构建器。
+
+use core::marker::PhantomData;
+
+use lock_api::RawMutex;
+
+use crate::reclaim::{DropQueue, OverflowPolicy};
+use crate::sync::Arc;
+use crate::{BacklogPolicy, DefaultRawMutex, LfrLock, LfrLockFactory, Shared, Strategy};
+
+#[cfg(feature = "std")]
+use std::borrow::Cow;
+
+#[cfg(not(feature = "std"))]
+use alloc::borrow::Cow;
+
+/// Builder for `LfrLock` and `LfrLockFactory` - Configures optional behaviour
+///
+/// Created by [`LfrLock::builder`]. Every option defaults to the behaviour of
+/// [`LfrLock::new`].
+///
+/// `LfrLock` 和 `LfrLockFactory` 的构建器 - 配置可选行为
+///
+/// 由 [`LfrLock::builder`] 创建。所有选项的默认值与 [`LfrLock::new`] 的行为一致。
+pub struct LfrLockBuilder<T: 'static, R: RawMutex = DefaultRawMutex> {
+    pub(crate) strategy: Strategy,
+    pub(crate) poisoning: bool,
+    pub(crate) skip_equal: Option<fn(&T, &T) -> bool>,
+    /// Capacity and policy of the drop queue, if retired values are queued.
+    ///
+    /// 如果退休的值需要排队，则为 drop 队列的容量和策略。
+    pub(crate) drop_queue: Option<(usize, OverflowPolicy)>,
+    pub(crate) backlog: Option<(usize, BacklogPolicy)>,
+    /// Starts the background thread, set by [`background_reclaimer`](Self::background_reclaimer).
+    ///
+    /// 启动后台线程，由 [`background_reclaimer`](Self::background_reclaimer) 设置。
+    #[cfg(feature = "std")]
+    pub(crate) spawn_reclaimer: Option<fn(&Arc<DropQueue<T>>)>,
+    /// Set by [`name`](Self::name).
+    ///
+    /// 由 [`name`](Self::name) 设置。
+    pub(crate) name: Option<Cow<'static, str>>,
+    raw_mutex: PhantomData<fn() -> R>,
+}
+
+impl<T: 'static> LfrLockBuilder<T> {
+    #[inline]
+    pub(crate) fn new() -> Self {
+        LfrLockBuilder {
+            strategy: Strategy::default(),
+            poisoning: false,
+            skip_equal: None,
+            drop_queue: None,
+            backlog: None,
+            #[cfg(feature = "std")]
+            spawn_reclaimer: None,
+            name: None,
+            raw_mutex: PhantomData,
+        }
+    }
+}
+
+impl<T: 'static, R: RawMutex> LfrLockBuilder<T, R> {
+    /// Choose the raw mutex that serializes writers.
+    ///
+    /// Defaults to [`DefaultRawMutex`]: `parking_lot::RawMutex` with `std`; otherwise
+    /// `spin::Mutex`, or `CriticalSectionRawMutex` with `critical-section`. Any
+    /// [`lock_api::RawMutex`] works, e.g. a spin lock with backoff or an instrumented mutex.
+    /// Readers never touch it.
+    ///
+    /// 选择串行化写者的原始互斥锁。
+    ///
+    /// 默认为 [`DefaultRawMutex`]：在 `std` 下是 `parking_lot::RawMutex`；否则为 `spin::Mutex`，
+    /// 启用 `critical-section` 时为 `CriticalSectionRawMutex`。任何 [`lock_api::RawMutex`]
+    /// 都可以使用，例如带退避的自旋锁或带统计的互斥锁。
+    /// 读者永远不会接触它。
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use lfrlock::{DefaultRawMutex, LfrLock};
+    ///
+    /// let lock: LfrLock<i32, DefaultRawMutex> = LfrLock::builder()
+    ///     .raw_mutex::<DefaultRawMutex>()
+    ///     .build(0);
+    /// lock.store(1);
+    /// assert_eq!(*lock.read(), 1);
+    /// ```
+    #[inline]
+    pub fn raw_mutex<M: RawMutex>(self) -> LfrLockBuilder<T, M> {
+        LfrLockBuilder {
+            strategy: self.strategy,
+            poisoning: self.poisoning,
+            skip_equal: self.skip_equal,
+            drop_queue: self.drop_queue,
+            backlog: self.backlog,
+            #[cfg(feature = "std")]
+            spawn_reclaimer: self.spawn_reclaimer,
+            name: self.name,
+            raw_mutex: PhantomData,
+        }
+    }
+
+    /// Choose the read/write preference of this lock.
+    ///
+    /// Defaults to [`Strategy::default`], which only the `read-preferred` feature changes.
+    /// Setting it here is unaffected by features that other crates enable.
+    ///
+    /// 选择此锁的读/写优先策略。
+    ///
+    /// 默认为 [`Strategy::default`]，只有 `read-preferred` feature 会改变它。
+    /// 在此设置不受其他 crate 启用的 feature 影响。
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use lfrlock::{LfrLock, Strategy};
+    ///
+    /// let config = LfrLock::builder()
+    ///     .strategy(Strategy::ReadPreferred)
+    ///     .build(String::from("config"));
+    /// assert_eq!(config.strategy(), Strategy::ReadPreferred);
+    /// ```
+    #[inline]
+    pub fn strategy(mut self, strategy: Strategy) -> Self {
+        self.strategy = strategy;
+        self
+    }
+
+    /// Name the lock in diagnostics.
+    ///
+    /// With the `deadlock-detection` feature, lock-order inversions report the lock under
+    /// this name. Unnamed locks are reported as `LfrLock#<id>`.
+    ///
+    /// 在诊断信息中为锁命名。
+    ///
+    /// 启用 `deadlock-detection` feature 时，加锁顺序颠倒的报告会使用此名称指代该锁。
+    /// 未命名的锁报告为 `LfrLock#<id>`。
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use lfrlock::LfrLock;
+    ///
+    /// let routes = LfrLock::builder().name("routes").build(Vec::<String>::new());
+    /// routes.write().push(String::from("/health"));
+    /// ```
+    #[inline]
+    pub fn name(mut self, name: impl Into<Cow<'static, str>>) -> Self {
+        self.name = Some(name.into());
+        self
+    }
+
+    /// Enable or disable poisoning (disabled by default).
+    ///
+    /// When enabled, a writer that panics while holding the write lock marks the lock
+    /// poisoned. [`LfrLock::read_checked`] and [`LfrLock::write_checked`] then return a
+    /// [`PoisonError`](crate::PoisonError) until [`LfrLock::clear_poison`] is called.
+    /// Panics can only be detected with `std`.
+    ///
+    /// 启用或禁用中毒（默认禁用）。
+    ///
+    /// 启用后，在持有写入锁时 panic 的写者会将锁标记为中毒。之后 [`LfrLock::read_checked`]
+    /// 和 [`LfrLock::write_checked`] 会返回 [`PoisonError`](crate::PoisonError)，
+    /// 直到调用 [`LfrLock::clear_poison`]。只有在 `std` 环境下才能检测 panic。
+    #[inline]
+    pub fn poisoning(mut self, enabled: bool) -> Self {
+        self.poisoning = enabled;
+        self
+    }
+
+    /// Skip commits whose new value equals the current one.
+    ///
+    /// Such commits neither bump the version nor wake subscribers. Applies to every commit
+    /// path (`store`, `update`, `WriteGuard`, ...).
+    ///
+    /// 跳过新值与当前值相等的提交。
+    ///
+    /// 这类提交既不会递增版本号，也不会唤醒订阅者。适用于所有提交路径
+    /// （`store`、`update`、`WriteGuard` 等）。
+    #[inline]
+    pub fn skip_equal(mut self) -> Self
+    where
+        T: PartialEq,
+    {
+        self.skip_equal = Some(T::eq);
+        self
+    }
+
+    /// Limit the number of retired values waiting for reclamation.
+    ///
+    /// A long-lived read guard keeps every value retired after it alive. Once `limit` retired
+    /// values are waiting and none of them can be collected, `policy` decides what the next
+    /// commit does. `limit` is raised to at least 3, as the latest retirements can only be
+    /// reclaimed after later commits. See [`LfrLock::backlog`].
+    ///
+    /// 限制等待回收的退休值数量。
+    ///
+    /// 长时间存活的读取守卫会使其之后退休的所有值保持存活。当有 `limit` 个退休值在等待且都无法回收时，
+    /// 由 `policy` 决定下一次提交的行为。`limit` 至少为 3，因为最近退休的值只能在之后的提交后回收。
+    /// 参见 [`LfrLock::backlog`]。
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use lfrlock::{BacklogPolicy, LfrLock, TryStoreError};
+    ///
+    /// let lock = LfrLock::builder()
+    ///     .max_backlog(3, BacklogPolicy::Reject)
+    ///     .build(0);
+    /// let guard = lock.read();
+    ///
+    /// // The guard pins every value retired from now on
+    /// let rejected = (1..10).find_map(|i| lock.try_store(i).err()).unwrap();
+    /// let TryStoreError::BacklogFull(rejected) = rejected else {
+    ///     unreachable!()
+    /// };
+    /// assert_eq!(rejected.backlog(), 3);
+    /// drop(guard);
+    /// ```
+    #[inline]
+    pub fn max_backlog(mut self, limit: usize, policy: BacklogPolicy) -> Self {
+        self.backlog = Some((limit.max(3), policy));
+        self
+    }
+
+    /// Queue retired values instead of dropping them on the writer's thread.
+    ///
+    /// Queued values are dropped by [`LfrLock::drain_retired`] or [`LfrLock::synchronize`].
+    /// At most `capacity` values wait in the queue; `policy` decides what happens to the next
+    /// one. Useful without `std`, where no background thread is available. Values handed to a
+    /// reclamation hook or owned by a [`Retired`](crate::Retired) handle bypass the queue.
+    ///
+    /// 将退休的值放入队列，而不是在写者线程上 drop。
+    ///
+    /// 排队的值由 [`LfrLock::drain_retired`] 或 [`LfrLock::synchronize`] drop。队列中最多等待
+    /// `capacity` 个值；`policy` 决定下一个值如何处理。适用于没有后台线程可用的 `no_std` 环境。
+    /// 交给回收钩子或归 [`Retired`](crate::Retired) 句柄所有的值会绕过该队列。
+    #[inline]
+    pub fn deferred_drop(mut self, capacity: usize, policy: OverflowPolicy) -> Self {
+        self.drop_queue = Some((capacity, policy));
+        #[cfg(feature = "std")]
+        {
+            self.spawn_reclaimer = None;
+        }
+        self
+    }
+
+    /// Drop retired values on a dedicated background thread.
+    ///
+    /// Like [`deferred_drop`](Self::deferred_drop), but a thread named `lfrlock-reclaimer`
+    /// drains the queue as soon as values arrive. The thread exits once the lock and all its
+    /// handles are gone.
+    ///
+    /// 在专用的后台线程上 drop 退休的值。
+    ///
+    /// 与 [`deferred_drop`](Self::deferred_drop) 相同，但名为 `lfrlock-reclaimer` 的线程会在值到达时
+    /// 立即清理队列。锁及其所有句柄销毁后，该线程退出。
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use lfrlock::{LfrLock, OverflowPolicy};
+    ///
+    /// let lock = LfrLock::builder()
+    ///     .background_reclaimer(1024, OverflowPolicy::DropInline)
+    ///     .build(vec![0u8; 4096]);
+    /// lock.store(vec![1u8; 4096]);
+    /// ```
+    #[cfg(feature = "std")]
+    #[inline]
+    pub fn background_reclaimer(mut self, capacity: usize, policy: OverflowPolicy) -> Self
+    where
+        T: Send + Sync,
+    {
+        self.drop_queue = Some((capacity, policy));
+        self.spawn_reclaimer = Some(crate::reclaim::spawn_reclaimer::<T>);
+        self
+    }
+
+    /// Create the drop queue and its background thread, if configured.
+    ///
+    /// 如果已配置，则创建 drop 队列及其后台线程。
+    pub(crate) fn make_drop_queue(&self) -> Option<Arc<DropQueue<T>>> {
+        let (capacity, policy) = self.drop_queue?;
+        let queue = Arc::new(DropQueue::new(capacity, policy));
+        #[cfg(feature = "std")]
+        if let Some(spawn) = self.spawn_reclaimer {
+            spawn(&queue);
+        }
+        Some(queue)
+    }
+
+    /// Build a lock with the configured options.
+    ///
+    /// 使用配置的选项构建锁。
+    #[inline]
+    pub fn build(self, initial: T) -> LfrLock<T, R> {
+        let (shared, local) = Shared::new(initial, &self);
+        LfrLock {
+            shared: Arc::new(shared),
+            local,
+        }
+    }
+
+    /// Build a factory with the configured options.
+    ///
+    /// 使用配置的选项构建工厂。
+    #[inline]
+    pub fn build_factory(self, initial: T) -> LfrLockFactory<T, R> {
+        let (shared, local) = Shared::new(initial, &self);
+        LfrLockFactory {
+            shared: Arc::new(shared),
+            reader: local.into_factory(),
+        }
+    }
+}
+
+impl<T: 'static> Default for LfrLockBuilder<T> {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
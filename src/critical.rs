@@ -0,0 +1,127 @@
+//! Writer mutex for single-core embedded targets.
+//!
+//! A spin lock can never be released while an interrupt handler spins on it, so a handler
+//! that writes while thread mode holds the lock hangs forever. Taking a critical section
+//! instead keeps such handlers from running until the write is done. Reads stay lock-free
+//! and may run in interrupt context.
+//!
+//! 用于单核嵌入式目标的写入互斥锁。
+//!
+//! 中断处理程序在自旋锁上自旋时，该锁永远无法被释放，因此在线程模式持有锁期间进行写入的处理程序会永远挂起。
+//! 改为进入临界区，可以使这类处理程序在写入完成之前无法运行。读取仍然无锁，并且可以在中断上下文中进行。
+
+use core::cell::UnsafeCell;
+use critical_section::RestoreState;
+
+use crate::sync::{AtomicBool, Ordering};
+
+/// CriticalSectionRawMutex - A `lock_api::RawMutex` held inside a critical section
+///
+/// Locking enters a critical section and unlocking leaves it, so writes are serialized
+/// against other cores and interrupt handlers alike. Requires a `critical-section`
+/// implementation, e.g. the one of `cortex-m` with its `critical-section-single-core`
+/// feature. Without `std`, it is the [`DefaultRawMutex`](crate::DefaultRawMutex) when the
+/// `critical-section` feature is enabled.
+///
+/// [`try_lock`](lock_api::RawMutex::try_lock) fails without entering the critical section
+/// while the lock is held. On implementations with several cores, another core may still take
+/// the lock between that check and entering, and then `try_lock` waits until it is released.
+/// The `try_*` writes of `LfrLock` therefore only never block with single-core
+/// implementations.
+///
+/// Critical sections must be left in reverse order of entering them: drop write guards of
+/// several such locks in reverse order of taking them. Locking again from the context that
+/// holds the lock panics instead of hanging.
+///
+/// CriticalSectionRawMutex - 在临界区内持有的 `lock_api::RawMutex`
+///
+/// 加锁时进入临界区，解锁时离开临界区，因此写入对其他核心和中断处理程序同样是串行的。
+/// 需要一个 `critical-section` 实现，例如启用 `critical-section-single-core` feature 的 `cortex-m`。
+/// 没有 `std` 时，如果启用了 `critical-section` feature，它就是 [`DefaultRawMutex`](crate::DefaultRawMutex)。
+///
+/// 锁被持有时，[`try_lock`](lock_api::RawMutex::try_lock) 会在不进入临界区的情况下失败。
+/// 在多核实现上，另一个核心仍可能在该检查与进入临界区之间获取锁，此时 `try_lock` 会等到锁被释放。
+/// 因此 `LfrLock` 的 `try_*` 写入只有在单核实现上才永不阻塞。
+///
+/// 临界区必须以进入时的相反顺序离开：多个此类锁的写入守卫应以获取时的相反顺序 drop。
+/// 在持有锁的上下文中再次加锁会 panic，而不是挂起。
+pub struct CriticalSectionRawMutex {
+    /// Only written inside the critical section.
+    ///
+    /// 仅在临界区内写入。
+    locked: AtomicBool,
+    /// State to restore when leaving the critical section, owned by the holder.
+    ///
+    /// 离开临界区时要恢复的状态，归持有者所有。
+    restore: UnsafeCell<RestoreState>,
+}
+
+impl CriticalSectionRawMutex {
+    /// Enter the critical section and take the lock, unless this context already holds it.
+    ///
+    /// 进入临界区并获取锁，除非本上下文已持有该锁。
+    #[inline]
+    fn enter(&self) -> bool {
+        let restore = unsafe { critical_section::acquire() };
+        if self.locked.load(Ordering::Relaxed) {
+            unsafe { critical_section::release(restore) };
+            return false;
+        }
+        self.locked.store(true, Ordering::Relaxed);
+        unsafe { *self.restore.get() = restore };
+        true
+    }
+}
+
+// SAFETY: `restore` is only accessed by the holder of the lock.
+// 安全性：`restore` 只会被锁的持有者访问。
+unsafe impl Sync for CriticalSectionRawMutex {}
+
+// SAFETY: the critical section is entered before `locked` is set and left after it is
+// cleared, and only one context can be inside it at a time besides nested entries, which
+// find `locked` set.
+// 安全性：临界区在设置 `locked` 之前进入，在清除之后离开。除嵌套进入外同一时刻只有一个上下文
+// 能位于其中，而嵌套进入会发现 `locked` 已被设置。
+unsafe impl lock_api::RawMutex for CriticalSectionRawMutex {
+    #[allow(clippy::declare_interior_mutable_const)]
+    const INIT: Self = CriticalSectionRawMutex {
+        locked: AtomicBool::new(false),
+        restore: UnsafeCell::new(RestoreState::invalid()),
+    };
+
+    // The critical section must be left in the context that entered it
+    // 临界区必须在进入它的上下文中离开
+    type GuardMarker = lock_api::GuardNoSend;
+
+    #[inline]
+    fn lock(&self) {
+        // Any other holder keeps us out of the critical section, so finding the lock held
+        // here means this context already holds it
+        // 其他持有者会阻止我们进入临界区，因此在这里发现锁已被持有意味着本上下文已持有该锁
+        if !self.enter() {
+            panic!("CriticalSectionRawMutex locked again by the context holding it");
+        }
+    }
+
+    #[inline]
+    fn try_lock(&self) -> bool {
+        // Entering the critical section would wait for a holder on another core to leave it
+        // 进入临界区会等待另一个核心上的持有者离开它
+        if self.locked.load(Ordering::Relaxed) {
+            return false;
+        }
+        self.enter()
+    }
+
+    #[inline]
+    unsafe fn unlock(&self) {
+        let restore = unsafe { *self.restore.get() };
+        self.locked.store(false, Ordering::Relaxed);
+        unsafe { critical_section::release(restore) };
+    }
+
+    #[inline]
+    fn is_locked(&self) -> bool {
+        self.locked.load(Ordering::Relaxed)
+    }
+}
@@ -0,0 +1,291 @@
+//! Lock-order checking for the `deadlock-detection` feature.
+//!
+//! Every thread keeps the stack of writer mutexes it holds. Taking another one while holding
+//! some adds an edge "held before acquired" to a global graph. An edge that closes a cycle
+//! means two code paths take the same locks in opposite orders, which can deadlock once they
+//! run at the same time, so it is reported right away with the sites of every acquisition
+//! involved, whether or not the threads ever met.
+//!
+//! Without the feature, [`Node`] has no size and every check does nothing.
+//!
+//! `deadlock-detection` feature 的加锁顺序检查。
+//!
+//! 每个线程都维护其持有的写入互斥锁的栈。在持有锁的同时获取另一个锁，会向全局图中添加一条
+//! “先持有、后获取”的边。形成环的边意味着两条代码路径以相反的顺序获取相同的锁，一旦它们同时运行
+//! 就可能死锁，因此会立即报告，并附带所涉及的每次获取的位置，无论这些线程是否真正相遇过。
+//!
+//! 没有该 feature 时，[`Node`] 不占空间，所有检查都不做任何事。
+
+#[cfg(feature = "deadlock-detection")]
+pub(crate) use enabled::Node;
+
+#[cfg(not(feature = "deadlock-detection"))]
+pub(crate) use disabled::Node;
+
+#[cfg(feature = "deadlock-detection")]
+mod enabled {
+    use core::cell::RefCell;
+    use core::fmt::{self, Write};
+    use core::panic::Location;
+    use core::sync::atomic::{AtomicUsize, Ordering};
+    use std::collections::BTreeMap;
+    use std::string::String;
+    use std::sync::{Mutex, PoisonError};
+    use std::vec::Vec;
+
+    type Site = &'static Location<'static>;
+
+    /// Source of lock identities, never reused so that edges of dropped locks cannot match.
+    ///
+    /// 锁标识的来源，永不重用，使已 drop 的锁的边无法匹配。
+    static NEXT_ID: AtomicUsize = AtomicUsize::new(1);
+
+    static GRAPH: Mutex<Graph> = Mutex::new(Graph {
+        names: BTreeMap::new(),
+        edges: BTreeMap::new(),
+    });
+
+    std::thread_local! {
+        /// Writer mutexes held by this thread, in acquisition order.
+        ///
+        /// 本线程持有的写入互斥锁，按获取顺序排列。
+        static HELD: RefCell<Vec<Held>> = const { RefCell::new(Vec::new()) };
+    }
+
+    struct Held {
+        id: usize,
+        site: Site,
+    }
+
+    /// An observed "held before acquired" order of two locks.
+    ///
+    /// 观察到的两个锁之间“先持有、后获取”的顺序。
+    #[derive(Clone, Copy)]
+    struct Edge {
+        held_at: Site,
+        acquired_at: Site,
+    }
+
+    struct Graph {
+        /// Names given with `LfrLockBuilder::name`.
+        ///
+        /// 通过 `LfrLockBuilder::name` 给出的名称。
+        names: BTreeMap<usize, String>,
+        /// `edges[a][b]`: `b` was acquired while holding `a`.
+        ///
+        /// `edges[a][b]`：在持有 `a` 时获取了 `b`。
+        edges: BTreeMap<usize, BTreeMap<usize, Edge>>,
+    }
+
+    impl Graph {
+        /// Edges of a path from `from` to `to`, if any.
+        ///
+        /// 从 `from` 到 `to` 的路径上的边（如果存在）。
+        fn path(&self, from: usize, to: usize) -> Option<Vec<(usize, usize, Edge)>> {
+            let mut parents = BTreeMap::new();
+            let mut stack = Vec::from([from]);
+            while let Some(node) = stack.pop() {
+                if node == to {
+                    let mut path = Vec::new();
+                    let mut node = to;
+                    while node != from {
+                        let (parent, edge) = parents[&node];
+                        path.push((parent, node, edge));
+                        node = parent;
+                    }
+                    path.reverse();
+                    return Some(path);
+                }
+                for (&next, &edge) in self.edges.get(&node).into_iter().flatten() {
+                    if next != from && !parents.contains_key(&next) {
+                        parents.insert(next, (node, edge));
+                        stack.push(next);
+                    }
+                }
+            }
+            None
+        }
+
+        fn name(&self, id: usize) -> Name<'_> {
+            Name(self.names.get(&id).map(String::as_str), id)
+        }
+
+        /// Describe the cycle closed by the new edge `held -> acquired`, given the existing
+        /// path from `acquired` back to `held`.
+        ///
+        /// 描述新边 `held -> acquired` 所形成的环，已知从 `acquired` 回到 `held` 的现有路径。
+        fn report(
+            &self,
+            held: usize,
+            acquired: usize,
+            new: Edge,
+            path: &[(usize, usize, Edge)],
+        ) -> String {
+            let mut report = String::new();
+            let _ = write!(
+                report,
+                "lock order inversion: {} -> {}",
+                self.name(held),
+                self.name(acquired)
+            );
+            for &(_, to, _) in path {
+                let _ = write!(report, " -> {}", self.name(to));
+            }
+            let _ = write!(
+                report,
+                "\n  this thread holds {} (acquired at {}) and is acquiring {} at {}",
+                self.name(held),
+                new.held_at,
+                self.name(acquired),
+                new.acquired_at,
+            );
+            for &(from, to, edge) in path {
+                let _ = write!(
+                    report,
+                    "\n  earlier, {} was held (acquired at {}) while acquiring {} at {}",
+                    self.name(from),
+                    edge.held_at,
+                    self.name(to),
+                    edge.acquired_at,
+                );
+            }
+            report
+        }
+    }
+
+    struct Name<'a>(Option<&'a str>, usize);
+
+    impl fmt::Display for Name<'_> {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            match self.0 {
+                Some(name) => write!(f, "{name:?}"),
+                None => write!(f, "LfrLock#{}", self.1),
+            }
+        }
+    }
+
+    fn graph() -> std::sync::MutexGuard<'static, Graph> {
+        GRAPH.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// Identity of one writer mutex in the lock-order graph.
+    ///
+    /// 一个写入互斥锁在加锁顺序图中的标识。
+    pub(crate) struct Node {
+        id: usize,
+    }
+
+    impl Node {
+        pub(crate) fn new(name: Option<&str>) -> Self {
+            let id = NEXT_ID.fetch_add(1, Ordering::Relaxed);
+            if let Some(name) = name {
+                graph().names.insert(id, String::from(name));
+            }
+            Node { id }
+        }
+
+        /// Record the order of this lock after the ones this thread holds, panicking if that
+        /// closes a cycle. Called before blocking on the lock.
+        ///
+        /// 记录此锁位于本线程所持有的锁之后的顺序，如果形成环则 panic。在阻塞等待该锁之前调用。
+        #[track_caller]
+        pub(crate) fn check_order(&self) {
+            let site = Location::caller();
+            let report = HELD
+                .try_with(|held| {
+                    let held = held.borrow();
+                    let mut held = held.iter().filter(|held| held.id != self.id).peekable();
+                    held.peek()?;
+
+                    let mut graph = graph();
+                    for held in held {
+                        // A known edge was acyclic when added, and no edge closing a cycle is
+                        // ever added
+                        // 已知的边在添加时无环，且形成环的边永远不会被添加
+                        if graph
+                            .edges
+                            .get(&held.id)
+                            .is_some_and(|edges| edges.contains_key(&self.id))
+                        {
+                            continue;
+                        }
+                        let edge = Edge {
+                            held_at: held.site,
+                            acquired_at: site,
+                        };
+                        if let Some(path) = graph.path(self.id, held.id) {
+                            return Some(graph.report(held.id, self.id, edge, &path));
+                        }
+                        graph
+                            .edges
+                            .entry(held.id)
+                            .or_default()
+                            .insert(self.id, edge);
+                    }
+                    None
+                })
+                .ok()
+                .flatten();
+
+            if let Some(report) = report {
+                panic!("{report}");
+            }
+        }
+
+        /// Push this lock onto the locks held by this thread.
+        ///
+        /// 将此锁压入本线程持有的锁中。
+        #[track_caller]
+        pub(crate) fn acquired(&self) {
+            let site = Location::caller();
+            let _ = HELD.try_with(|held| held.borrow_mut().push(Held { id: self.id, site }));
+        }
+
+        /// Remove this lock from the locks held by this thread, if it is there.
+        ///
+        /// 如果此锁在本线程持有的锁中，则将其移除。
+        pub(crate) fn released(&self) {
+            let _ = HELD.try_with(|held| {
+                let mut held = held.borrow_mut();
+                if let Some(index) = held.iter().rposition(|held| held.id == self.id) {
+                    held.remove(index);
+                }
+            });
+        }
+    }
+
+    impl Drop for Node {
+        fn drop(&mut self) {
+            let mut graph = graph();
+            graph.names.remove(&self.id);
+            graph.edges.remove(&self.id);
+            for edges in graph.edges.values_mut() {
+                edges.remove(&self.id);
+            }
+        }
+    }
+}
+
+#[cfg(not(feature = "deadlock-detection"))]
+mod disabled {
+    /// Identity of one writer mutex, not tracked without the `deadlock-detection` feature.
+    ///
+    /// 一个写入互斥锁的标识，没有 `deadlock-detection` feature 时不跟踪。
+    pub(crate) struct Node;
+
+    impl Node {
+        #[inline]
+        pub(crate) fn new(_name: Option<&str>) -> Self {
+            Node
+        }
+
+        #[inline]
+        pub(crate) fn check_order(&self) {}
+
+        #[inline]
+        pub(crate) fn acquired(&self) {}
+
+        #[inline]
+        pub(crate) fn released(&self) {}
+    }
+}
@@ -3,206 +3,1377 @@
 #[cfg(not(feature = "std"))]
 extern crate alloc;
 
+mod backlog;
+mod builder;
+#[cfg(feature = "critical-section")]
+mod critical;
+mod deadlock;
+mod mapped;
+mod poison;
+mod reclaim;
+#[cfg(feature = "reentrancy-check")]
+mod reentrancy;
+mod retired;
+mod snapshot;
+mod strategy;
+mod sync;
+mod transaction;
+mod try_write;
+#[cfg(feature = "std")]
+mod wait;
+mod watch;
+mod write_lock;
+
 use core::fmt;
+use core::future::Future;
 use core::mem::ManuallyDrop;
 use core::ops::{Deref, DerefMut};
-use smr_swap::{LocalReader, ReadGuard, SmrReader, SmrSwap};
+use core::pin::Pin;
+use core::task::{Context, Poll};
+use lock_api::RawMutex;
+use reclaim::{Reclaimer, SwapGuard};
+use strategy::{Factory, Local, Pinned, Swap};
+use sync::{Arc, AtomicBool, Ordering};
+use transaction::Prior;
+use watch::WatchList;
+use write_lock::{LockFuture, WriteLock, WriteLockGuard};
+
+pub use backlog::{BacklogFull, BacklogPolicy};
+pub use builder::LfrLockBuilder;
+#[cfg(feature = "critical-section")]
+pub use critical::CriticalSectionRawMutex;
+pub use lock_api;
+pub use lock_impl::DefaultRawMutex;
+pub use mapped::{MappedReadGuard, MappedWriteGuard};
+pub use poison::{LockResult, PoisonError};
+pub use reclaim::OverflowPolicy;
+#[cfg(feature = "reentrancy-check")]
+pub use reentrancy::set_context_id;
+pub use retired::Retired;
+pub use snapshot::{Snapshot, snapshot};
+pub use strategy::{AdaptiveConfig, Strategy};
+pub use transaction::{Transaction, transaction};
+pub use try_write::{TryStoreError, TryUpdateError, TryWriteError};
+#[cfg(feature = "std")]
+pub use wait::{Watchable, wait_any, wait_any_timeout};
+pub use watch::Subscriber;
 
 #[cfg(feature = "std")]
-use std::sync::Arc;
+use std::time::{Duration, Instant};
 
 #[cfg(not(feature = "std"))]
-use alloc::sync::Arc;
+use alloc::boxed::Box;
+
+/// LfrLock (Lock-Free Read Lock) - Reads never block, writes are serialized using Mutex
+///
+/// Similar to `std::sync::Mutex`, a unified type supports both read and write operations.
+/// Core features: Read operations are lock-free and never block; write operations involve copying old data, modifying, and then atomically replacing.
+///
+/// LfrLock (Lock-Free Read Lock) - 读取永不阻塞，写入使用 Mutex 串行化
+///
+/// 类似于 `std::sync::Mutex`，统一的类型同时支持读写操作。
+/// 核心特性：读取操作无锁且永不阻塞；写入操作涉及复制旧数据、修改、然后原子替换。
+pub struct LfrLock<T: 'static, R: RawMutex = DefaultRawMutex> {
+    shared: Arc<Shared<T, R>>,
+    local: Local<Slot<T>>,
+}
+
+/// State shared by every handle and factory of the same lock.
+///
+/// 同一个锁的所有句柄和工厂共享的状态。
+struct Shared<T: 'static, R: RawMutex> {
+    swap: WriteLock<Swap<Slot<T>>, R>,
+    watchers: WatchList,
+    /// Equality used to skip no-op commits, set by [`LfrLockBuilder::skip_equal`].
+    ///
+    /// 用于跳过无效提交的相等比较，由 [`LfrLockBuilder::skip_equal`] 设置。
+    skip_equal: Option<fn(&T, &T) -> bool>,
+    /// Limit and policy for retired values, set by [`LfrLockBuilder::max_backlog`].
+    ///
+    /// 退休值的上限和策略，由 [`LfrLockBuilder::max_backlog`] 设置。
+    backlog: Option<(usize, BacklogPolicy)>,
+    /// Whether writer wait times are measured for [`Strategy::Adaptive`].
+    ///
+    /// 是否为 [`Strategy::Adaptive`] 测量写者等待时间。
+    #[cfg_attr(not(feature = "std"), allow(dead_code))]
+    adaptive: bool,
+    /// Set by [`LfrLock::close`], only changed with the writer Mutex held.
+    ///
+    /// 由 [`LfrLock::close`] 设置，仅在持有写入 Mutex 时修改。
+    closed: AtomicBool,
+    reclaimer: Arc<Reclaimer<T>>,
+}
+
+/// A published value together with the version of the commit that produced it.
+///
+/// The value sits behind an `Arc` so that a retired value can be handed out as a
+/// [`Retired`] and unwrapped once the reclaimer has dropped its copy.
+///
+/// 已发布的值，以及产生它的提交的版本号。
+///
+/// 值存放在 `Arc` 中，使退休的值可以作为 [`Retired`] 交出，并在回收器丢弃其副本后取出。
+struct Slot<T: 'static> {
+    /// Only taken out when the slot is dropped.
+    ///
+    /// 仅在槽被 drop 时取出。
+    value: ManuallyDrop<Arc<T>>,
+    version: usize,
+    reclaimer: Arc<Reclaimer<T>>,
+    /// The value this slot replaced, set for slots stored by a [`transaction`].
+    ///
+    /// 此槽替换掉的值，为由 [`transaction`] 存储的槽设置。
+    prior: Option<Prior<T>>,
+}
+
+impl<T: 'static> Slot<T> {
+    /// Copy of this slot sharing the same value and version.
+    ///
+    /// 共享同一个值和版本号的槽副本。
+    #[inline]
+    fn republish(&self) -> Self {
+        Slot {
+            value: ManuallyDrop::new(Arc::clone(&self.value)),
+            version: self.version,
+            reclaimer: self.reclaimer.clone(),
+            prior: None,
+        }
+    }
+
+    /// Whether readers still see the prior value, as the transaction that stored this slot
+    /// has not committed yet.
+    ///
+    /// 读者是否仍看到之前的值，因为存储此槽的事务尚未提交。
+    #[inline]
+    fn shows_prior(&self) -> bool {
+        self.prior.as_ref().is_some_and(Prior::is_pending)
+    }
+
+    /// Version of the value readers see.
+    ///
+    /// 读者看到的值的版本号。
+    #[inline]
+    fn visible_version(&self) -> usize {
+        match &self.prior {
+            Some(prior) if prior.is_pending() => prior.version,
+            _ => self.version,
+        }
+    }
+}
+
+/// Create a reader handle with `create`.
+///
+/// smr-swap registers readers under a spin lock that writers also take while collecting.
+/// With the `critical-section` feature and without `std`, registration runs inside a
+/// critical section, so an interrupt handler that writes never spins on that lock.
+///
+/// 使用 `create` 创建读者句柄。
+///
+/// smr-swap 在自旋锁下注册读者，写者在回收时也会获取该锁。启用 `critical-section` feature
+/// 且没有 `std` 时，注册在临界区内进行，因此进行写入的中断处理程序永远不会在该锁上自旋。
+#[inline]
+fn register_reader<L>(create: impl FnOnce() -> L) -> L {
+    #[cfg(all(feature = "critical-section", not(feature = "std")))]
+    return critical_section::with(|_| create());
+    #[cfg(not(all(feature = "critical-section", not(feature = "std"))))]
+    create()
+}
+
+impl<T: 'static> Drop for Slot<T> {
+    #[inline]
+    fn drop(&mut self) {
+        // Hand the reference over instead of cloning it, so the reclaimer holds the last one
+        // 移交引用而不是克隆，使回收器持有最后一个引用
+        let value = unsafe { ManuallyDrop::take(&mut self.value) };
+        self.reclaimer.retire(self.version, value);
+        if let Some(prior) = self.prior.take() {
+            self.reclaimer.retire(prior.version, prior.value);
+        }
+    }
+}
+
+impl<T: 'static, R: RawMutex> Drop for Shared<T, R> {
+    #[inline]
+    fn drop(&mut self) {
+        // Nobody can drain the queue anymore, slots dropped from here on are dropped inline
+        // 已无人能清理队列，此后被 drop 的槽会被直接 drop
+        self.reclaimer.close();
+    }
+}
+
+impl<T: 'static, R: RawMutex> Shared<T, R> {
+    #[inline]
+    fn new(initial: T, builder: &LfrLockBuilder<T, R>) -> (Self, Local<Slot<T>>) {
+        let reclaimer = Arc::new(Reclaimer::new(builder.make_drop_queue()));
+        let swap = Swap::new(
+            Slot {
+                value: ManuallyDrop::new(Arc::new(initial)),
+                version: 0,
+                reclaimer: reclaimer.clone(),
+                prior: None,
+            },
+            builder.strategy,
+        );
+        let local = register_reader(|| swap.local_reader());
+        let shared = Shared {
+            swap: WriteLock::new(swap, builder.poisoning, builder.name.as_deref()),
+            watchers: WatchList::new(),
+            skip_equal: builder.skip_equal,
+            backlog: builder.backlog.clone(),
+            adaptive: matches!(builder.strategy, Strategy::Adaptive(_)),
+            closed: AtomicBool::new(false),
+            reclaimer,
+        };
+        (shared, local)
+    }
+
+    /// Acquire the writer Mutex.
+    ///
+    /// 获取写入 Mutex。
+    #[inline]
+    #[track_caller]
+    fn lock(&self) -> SwapGuard<'_, T, R> {
+        #[cfg(feature = "std")]
+        if self.adaptive {
+            // The adaptive strategy weighs how long writers wait
+            // 自适应策略会考量写者的等待时间
+            let start = Instant::now();
+            let mut swap = self.guard(self.swap.lock());
+            swap.record_wait(start.elapsed());
+            return swap;
+        }
+        self.guard(self.swap.lock())
+    }
+
+    /// Try to acquire the writer Mutex without blocking.
+    ///
+    /// 尝试在不阻塞的情况下获取写入 Mutex。
+    #[inline]
+    #[track_caller]
+    fn try_lock(&self) -> Option<SwapGuard<'_, T, R>> {
+        self.swap.try_lock().map(|guard| self.guard(guard))
+    }
+
+    /// Acquire the writer Mutex to write a value.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the lock has been closed.
+    ///
+    /// 获取写入 Mutex 以写入值。
+    ///
+    /// # Panics
+    ///
+    /// 如果锁已关闭则 panic。
+    #[inline]
+    #[track_caller]
+    fn write_lock(&self) -> SwapGuard<'_, T, R> {
+        self.check_open(self.lock())
+    }
+
+    /// Try to acquire the writer Mutex to write a value, without blocking.
+    ///
+    /// 尝试在不阻塞的情况下获取写入 Mutex 以写入值。
+    #[inline]
+    #[track_caller]
+    fn try_write_lock(&self) -> Result<SwapGuard<'_, T, R>, TryWriteError> {
+        let swap = self.try_lock().ok_or_else(|| self.busy())?;
+        self.open(swap)
+    }
+
+    /// Acquire the writer Mutex to write a value, giving up once `timeout` has passed.
+    ///
+    /// 获取写入 Mutex 以写入值，超过 `timeout` 后放弃。
+    #[cfg(feature = "std")]
+    #[inline]
+    #[track_caller]
+    fn write_lock_timeout(&self, timeout: Duration) -> Result<SwapGuard<'_, T, R>, TryWriteError> {
+        let swap = self
+            .swap
+            .lock_timeout(timeout)
+            .ok_or_else(|| match self.busy() {
+                TryWriteError::WouldBlock => TryWriteError::Timeout,
+                error => error,
+            })?;
+        self.open(self.guard(swap))
+    }
+
+    /// Why the writer Mutex could not be taken right away.
+    ///
+    /// 写入 Mutex 无法立即获取的原因。
+    #[inline]
+    fn busy(&self) -> TryWriteError {
+        if self.swap.is_held_by_current_thread() {
+            TryWriteError::Reentrant
+        } else {
+            TryWriteError::WouldBlock
+        }
+    }
+
+    /// Hand the guard back unless the lock has been closed.
+    ///
+    /// 除非锁已关闭，否则交回守卫。
+    #[inline]
+    fn open<'a>(&self, swap: SwapGuard<'a, T, R>) -> Result<SwapGuard<'a, T, R>, TryWriteError> {
+        if self.closed.load(Ordering::Relaxed) {
+            return Err(TryWriteError::Closed);
+        }
+        Ok(swap)
+    }
+
+    /// Hand the guard back, or release it and panic if the lock has been closed.
+    ///
+    /// 交回守卫；如果锁已关闭，则释放守卫并 panic。
+    #[inline]
+    fn check_open<'a>(&self, swap: SwapGuard<'a, T, R>) -> SwapGuard<'a, T, R> {
+        match self.open(swap) {
+            Ok(swap) => swap,
+            // The guard is released before panicking, so the lock is not poisoned
+            // 守卫在 panic 之前已被释放，因此锁不会中毒
+            Err(_) => panic!("write to a closed LfrLock"),
+        }
+    }
+
+    /// Wrap a writer Mutex guard so that reclaimed values reach the hook after unlocking.
+    ///
+    /// 包装写入 Mutex 守卫，使被回收的值在解锁后交给钩子。
+    #[inline]
+    fn guard<'a>(&'a self, guard: WriteLockGuard<'a, Swap<Slot<T>>, R>) -> SwapGuard<'a, T, R> {
+        SwapGuard::new(guard, &self.reclaimer)
+    }
+
+    /// Publish `value` as the next version, wake subscribers and return that version.
+    ///
+    /// With `skip_equal`, a value equal to the current one is dropped instead and the current
+    /// version is returned. Must be called with the writer Mutex held.
+    ///
+    /// 将 `value` 作为下一个版本发布，唤醒订阅者并返回该版本号。
+    ///
+    /// 启用 `skip_equal` 时，与当前值相等的值会被丢弃，并返回当前版本号。
+    /// 必须在持有写入 Mutex 时调用。
+    #[inline]
+    fn publish(&self, swap: &mut Swap<Slot<T>>, value: T) -> usize {
+        let current = swap.get();
+        if let Some(eq) = self.skip_equal
+            && eq(&current.value, &value)
+        {
+            return current.version;
+        }
+
+        self.install(swap, value)
+    }
+
+    /// Store `value` as the next version and wake subscribers, without `skip_equal`.
+    ///
+    /// Must be called with the writer Mutex held.
+    ///
+    /// 将 `value` 存储为下一个版本并唤醒订阅者，不进行 `skip_equal` 检查。
+    ///
+    /// 必须在持有写入 Mutex 时调用。
+    #[inline]
+    fn install(&self, swap: &mut Swap<Slot<T>>, value: T) -> usize {
+        self.make_room(swap);
+        let version = swap.get().version.wrapping_add(1);
+        swap.store(Slot {
+            value: ManuallyDrop::new(Arc::new(value)),
+            version,
+            reclaimer: self.reclaimer.clone(),
+            prior: None,
+        });
+        swap.record_commit(Slot::republish);
+        self.watchers.notify_all();
+        version
+    }
+
+    /// Whether the backlog is at its limit, even after collecting what readers have left.
+    ///
+    /// 即使回收了读者已离开的值之后，积压是否仍达到上限。
+    fn is_full(&self, swap: &mut Swap<Slot<T>>) -> bool {
+        let Some((limit, _)) = self.backlog else {
+            return false;
+        };
+        if swap.garbage_count() < limit {
+            return false;
+        }
+        swap.collect();
+        swap.garbage_count() >= limit
+    }
+
+    /// Apply the backlog policy before a store. Must be called with the writer Mutex held.
+    ///
+    /// `Reject` is handled by [`LfrLock::try_store`], every other commit goes through.
+    ///
+    /// 在存储之前应用积压策略。必须在持有写入 Mutex 时调用。
+    ///
+    /// `Reject` 由 [`LfrLock::try_store`] 处理，其他提交照常进行。
+    fn make_room(&self, swap: &mut Swap<Slot<T>>) {
+        if !self.is_full(swap) {
+            return;
+        }
+        match &self.backlog {
+            Some((_, BacklogPolicy::Block)) => {
+                // The epoch is fixed, so republishing stops after the first round
+                // 纪元是固定的，因此重新发布在第一轮之后就会停止
+                let epoch = swap.version().wrapping_sub(1);
+                while self.is_full(swap) {
+                    self.reclaim_through(swap, epoch);
+                    #[cfg(feature = "std")]
+                    std::thread::yield_now();
+                    #[cfg(not(feature = "std"))]
+                    core::hint::spin_loop();
+                }
+            }
+            Some((_, BacklogPolicy::Callback(callback))) => callback(swap.garbage_count()),
+            _ => {}
+        }
+    }
+
+    /// Reclaim every retired value that readers have moved past, including the value
+    /// retired at smr epoch `epoch`.
+    ///
+    /// smr only reclaims values retired at least two stores ago, so this first advances the
+    /// epoch with stores that republish the current slot unchanged. Must be called with the
+    /// writer Mutex held.
+    ///
+    /// 回收读者已经越过的所有退休值，包括在 smr 纪元 `epoch` 退休的值。
+    ///
+    /// smr 只回收至少两次存储之前退休的值，因此这里先通过原样重新发布当前槽的存储来推进纪元。
+    /// 必须在持有写入 Mutex 时调用。
+    fn reclaim_through(&self, swap: &mut Swap<Slot<T>>, epoch: usize) {
+        while swap.version().wrapping_sub(epoch) < 3 {
+            let republished = swap.get().republish();
+            swap.store(republished);
+        }
+        swap.collect();
+    }
+
+    /// Wait until every value retired before this call has been reclaimed.
+    ///
+    /// The writer Mutex is only held for each check, so writers keep making progress.
+    /// `keep_waiting` is asked before every retry; returns `false` if it gave up.
+    ///
+    /// 等待直到本次调用之前退休的所有值都已被回收。
+    ///
+    /// 写入 Mutex 仅在每次检查时持有，因此写者可以继续推进。
+    /// 每次重试前都会询问 `keep_waiting`；如果它放弃则返回 `false`。
+    #[track_caller]
+    fn synchronize_with(&self, mut keep_waiting: impl FnMut() -> bool) -> bool {
+        let mut since = None;
+        loop {
+            let done = {
+                let mut swap = self.lock();
+                let since = *since.get_or_insert(swap.version());
+                swap.garbage_count() == 0 || {
+                    self.reclaim_through(&mut swap, since.wrapping_sub(1));
+                    !swap.holds_retired_before(since)
+                }
+            };
+            if done {
+                // Values reclaimed into the drop queue are not dropped yet
+                // 被回收到 drop 队列中的值尚未被 drop
+                self.reclaimer.drain_queue();
+                return true;
+            }
+
+            if !keep_waiting() {
+                return false;
+            }
+
+            #[cfg(feature = "std")]
+            std::thread::yield_now();
+            #[cfg(not(feature = "std"))]
+            core::hint::spin_loop();
+        }
+    }
+}
+
+impl<T: 'static> LfrLock<T> {
+    /// Create a new LfrLock
+    ///
+    /// 创建新的 LfrLock
+    #[inline]
+    pub fn new(initial: T) -> Self {
+        Self::builder().build(initial)
+    }
+
+    /// Create a builder for configuring optional behaviour such as poisoning.
+    ///
+    /// 创建用于配置可选行为（例如中毒）的构建器。
+    #[inline]
+    pub fn builder() -> LfrLockBuilder<T> {
+        LfrLockBuilder::new()
+    }
+}
+
+impl<T: 'static, R: RawMutex> LfrLock<T, R> {
+    /// Store a new value, making it visible to readers.
+    ///
+    /// The old value is retired and will be garbage collected when safe.
+    ///
+    /// 存储新值，使其对读者可见。
+    ///
+    /// 旧值已退休，将在安全时被垃圾回收。
+    #[inline]
+    #[track_caller]
+    pub fn store(&self, new_value: T) {
+        let mut swap = self.shared.write_lock();
+        self.shared.publish(&mut swap, new_value);
+    }
+
+    /// Store a new value unless that would wait, returning the current version.
+    ///
+    /// Never blocks: fails with [`TryStoreError::Lock`] if the write lock is busy or the lock
+    /// is closed. With [`BacklogPolicy::Reject`], also fails with
+    /// [`TryStoreError::BacklogFull`] if the limit set by [`LfrLockBuilder::max_backlog`] is
+    /// reached even after collecting. Either way `new_value` is given back.
+    ///
+    /// 除非需要等待，否则存储新值，并返回当前版本号。
+    ///
+    /// 永不阻塞：如果写入锁正忙或锁已关闭，则以 [`TryStoreError::Lock`] 失败。使用
+    /// [`BacklogPolicy::Reject`] 时，如果在回收之后仍达到 [`LfrLockBuilder::max_backlog`] 设置的上限，
+    /// 也会以 [`TryStoreError::BacklogFull`] 失败。无论哪种情况，`new_value` 都会被原样返回。
+    #[inline]
+    #[track_caller]
+    pub fn try_store(&self, new_value: T) -> Result<usize, TryStoreError<T>> {
+        let mut swap = match self.shared.try_write_lock() {
+            Ok(swap) => swap,
+            Err(error) => return Err(TryStoreError::Lock(error, new_value)),
+        };
+        if let Some((_, BacklogPolicy::Reject)) = self.shared.backlog
+            && self.shared.is_full(&mut swap)
+        {
+            let full = BacklogFull::new(new_value, swap.garbage_count());
+            return Err(TryStoreError::BacklogFull(full));
+        }
+        Ok(self.shared.publish(&mut swap, new_value))
+    }
+
+    /// Like [`try_update`](Self::try_update), unless that would wait.
+    ///
+    /// Never blocks: fails with [`TryUpdateError::Lock`] without running `f` if the write
+    /// lock is busy or the lock is closed. An error of `f` is handed back as
+    /// [`TryUpdateError::Aborted`], leaving the value untouched.
+    ///
+    /// 与 [`try_update`](Self::try_update) 相同，除非需要等待。
+    ///
+    /// 永不阻塞：如果写入锁正忙或锁已关闭，则以 [`TryUpdateError::Lock`] 失败且不运行 `f`。
+    /// `f` 的错误以 [`TryUpdateError::Aborted`] 原样返回，值保持不变。
+    #[inline]
+    #[track_caller]
+    pub fn try_lock_update<F, E>(&self, f: F) -> Result<usize, TryUpdateError<E>>
+    where
+        F: FnOnce(&T) -> Result<T, E>,
+    {
+        let mut swap = self.shared.try_write_lock().map_err(TryUpdateError::Lock)?;
+        let new_value = f(&swap.get().value).map_err(TryUpdateError::Aborted)?;
+        Ok(self.shared.publish(&mut swap, new_value))
+    }
+
+    /// Store a new value only if the current version equals `expected_version`.
+    ///
+    /// Returns the new version on success, or gives `new_value` back if another
+    /// commit happened in between.
+    ///
+    /// 仅当当前版本等于 `expected_version` 时存储新值。
+    ///
+    /// 成功时返回新版本号；如果期间有其他提交发生，则将 `new_value` 原样返回。
+    #[inline]
+    #[track_caller]
+    pub fn compare_and_store(&self, expected_version: usize, new_value: T) -> Result<usize, T> {
+        let mut swap = self.shared.write_lock();
+        if swap.get().version != expected_version {
+            return Err(new_value);
+        }
+        Ok(self.shared.publish(&mut swap, new_value))
+    }
+
+    /// Atomically swap the current value with a new one.
+    ///
+    /// Returns the old value.
+    ///
+    /// 原子地将当前值与新值交换。
+    ///
+    /// 返回旧的值。
+    #[inline]
+    #[track_caller]
+    pub fn swap(&self, new_value: T) -> T
+    where
+        T: Clone,
+    {
+        let mut swap = self.shared.write_lock();
+        let old_value = T::clone(&swap.get().value);
+        self.shared.publish(&mut swap, new_value);
+        old_value
+    }
+
+    /// Swap in a new value without requiring `T: Clone`.
+    ///
+    /// Returns a [`Retired`] handle to the old value. The handle gives shared access right
+    /// away and turns into an owned `T` once no reader can see the old value anymore.
+    ///
+    /// 在不要求 `T: Clone` 的情况下换入新值。
+    ///
+    /// 返回旧值的 [`Retired`] 句柄。该句柄立即提供共享访问，并在没有读者还能看到旧值时
+    /// 转换为拥有所有权的 `T`。
+    #[inline]
+    #[track_caller]
+    pub fn swap_retired(&self, new_value: T) -> Retired<T, R> {
+        let mut swap = self.shared.write_lock();
+        let old_value = Arc::clone(&swap.get().value);
+        let old_version = swap.get().version;
+        let epoch = swap.version();
+        // Always store, the old value must leave the lock even if it equals the new one
+        // 总是存储，即使旧值与新值相等，它也必须离开锁
+        self.shared.install(&mut swap, new_value);
+        Retired::new(old_value, self.shared.clone(), old_version, epoch)
+    }
+
+    /// Update the value using a closure.
+    ///
+    /// The closure receives the current value and should return the new value.
+    ///
+    /// 使用闭包更新值。
+    ///
+    /// 闭包接收当前值并应返回新值。
+    #[inline]
+    #[track_caller]
+    pub fn update<F>(&self, f: F)
+    where
+        F: FnOnce(&T) -> T,
+    {
+        let mut swap = self.shared.write_lock();
+        let new_value = f(&swap.get().value);
+        self.shared.publish(&mut swap, new_value);
+    }
+
+    /// Update the value using a closure only if the current version equals `expected_version`.
+    ///
+    /// The closure is not called on a version mismatch.
+    /// Returns the new version on success, or the current version on mismatch.
+    ///
+    /// 仅当当前版本等于 `expected_version` 时使用闭包更新值。
+    ///
+    /// 版本不匹配时不会调用闭包。
+    /// 成功时返回新版本号，不匹配时返回当前版本号。
+    #[inline]
+    #[track_caller]
+    pub fn compare_and_update<F>(&self, expected_version: usize, f: F) -> Result<usize, usize>
+    where
+        F: FnOnce(&T) -> T,
+    {
+        let mut swap = self.shared.write_lock();
+        let current = swap.get();
+        if current.version != expected_version {
+            return Err(current.version);
+        }
+        let new_value = f(&current.value);
+        Ok(self.shared.publish(&mut swap, new_value))
+    }
+
+    /// Update the value using a fallible closure.
+    ///
+    /// On `Ok` the returned value is published and its version is returned. On `Err` the
+    /// current value stays untouched and the error is handed back.
+    ///
+    /// 使用可能失败的闭包更新值。
+    ///
+    /// 返回 `Ok` 时发布返回的值并返回其版本号。返回 `Err` 时当前值保持不变，错误被原样返回。
+    #[inline]
+    #[track_caller]
+    pub fn try_update<F, E>(&self, f: F) -> Result<usize, E>
+    where
+        F: FnOnce(&T) -> Result<T, E>,
+    {
+        let mut swap = self.shared.write_lock();
+        let new_value = f(&swap.get().value)?;
+        Ok(self.shared.publish(&mut swap, new_value))
+    }
+
+    /// Update the value using a closure that also produces a result for the caller.
+    ///
+    /// The closure returns the new value together with the result, which is returned
+    /// after the new value has been published.
+    ///
+    /// 使用同时为调用者产生结果的闭包更新值。
+    ///
+    /// 闭包返回新值以及结果，结果在新值发布后返回。
+    #[inline]
+    #[track_caller]
+    pub fn update_with<F, U>(&self, f: F) -> U
+    where
+        F: FnOnce(&T) -> (T, U),
+    {
+        let mut swap = self.shared.write_lock();
+        let (new_value, result) = f(&swap.get().value);
+        self.shared.publish(&mut swap, new_value);
+        result
+    }
+
+    /// Mutate a copy of the value in place and return the closure's result - Requires T to implement Clone
+    ///
+    /// Runs through a [`WriteGuard`], so the copy is committed when the closure returns and
+    /// discarded if it panics.
+    ///
+    /// 就地修改值的副本并返回闭包的结果 - 需要 T 实现 Clone
+    ///
+    /// 通过 [`WriteGuard`] 执行，因此闭包返回时提交副本，闭包 panic 时丢弃副本。
+    #[inline]
+    #[track_caller]
+    pub fn write_with<F, U>(&self, f: F) -> U
+    where
+        T: Clone,
+        F: FnOnce(&mut T) -> U,
+    {
+        let mut guard = self.write();
+        f(&mut guard)
+    }
+
+    /// Update the value using a closure only if the current value satisfies `pred`.
+    ///
+    /// `pred` and `f` see the same value. Returns the new version on success, or `None`
+    /// if `pred` rejected the value, in which case `f` is not called.
+    ///
+    /// 仅当当前值满足 `pred` 时使用闭包更新值。
+    ///
+    /// `pred` 和 `f` 看到的是同一个值。成功时返回新版本号，如果 `pred` 拒绝了该值则返回
+    /// `None`，此时不会调用 `f`。
+    #[inline]
+    #[track_caller]
+    pub fn update_if<P, F>(&self, pred: P, f: F) -> Option<usize>
+    where
+        P: FnOnce(&T) -> bool,
+        F: FnOnce(&T) -> T,
+    {
+        let mut swap = self.shared.write_lock();
+        let current = &swap.get().value;
+        if !pred(current) {
+            return None;
+        }
+        let new_value = f(current);
+        Some(self.shared.publish(&mut swap, new_value))
+    }
+
+    /// Read-Copy-Update: compute the new value outside the writer Mutex and publish it
+    /// only if no other commit happened in between, retrying otherwise.
+    ///
+    /// The writer Mutex is only held for the version check and the pointer swap, so a slow
+    /// closure never blocks other writers. The closure may be called several times under
+    /// contention and should be cheap and free of side effects.
+    /// Returns a guard to the value that was replaced.
+    ///
+    /// 读取-复制-更新：在写入 Mutex 之外计算新值，仅当期间没有其他提交时才发布，否则重试。
+    ///
+    /// 写入 Mutex 仅在版本检查和指针交换期间持有，因此缓慢的闭包永远不会阻塞其他写者。
+    /// 在竞争情况下闭包可能被调用多次，应当开销较小且无副作用。
+    /// 返回被替换的值的守卫。
+    #[inline]
+    #[track_caller]
+    pub fn rcu<F>(&self, mut f: F) -> ReadGuard<'_, T>
+    where
+        F: FnMut(&T) -> T,
+    {
+        loop {
+            let current = self.read();
+            let new_value = f(&current);
+            if self.compare_and_store(current.version(), new_value).is_ok() {
+                return current;
+            }
+            core::hint::spin_loop();
+        }
+    }
+
+    /// Apply a closure function to the current value and return a guard to the new value.
+    ///
+    /// The closure receives a reference to the current value and returns a new value.
+    /// The guard is taken while the write lock is still held, so it holds exactly the value
+    /// installed by this call, even under concurrent writers.
+    ///
+    /// 对当前值应用闭包函数并返回新值的守卫。
+    ///
+    /// 闭包接收当前值的引用，返回新值。
+    /// 守卫在仍持有写入锁时获取，因此即使存在并发写者，它持有的也正是本次调用写入的值。
+    #[inline]
+    #[track_caller]
+    pub fn update_and_fetch<F>(&self, f: F) -> ReadGuard<'_, T>
+    where
+        F: FnOnce(&T) -> T,
+    {
+        let mut swap = self.shared.write_lock();
+        let new_value = f(&swap.get().value);
+        self.shared.publish(&mut swap, new_value);
+        self.read()
+    }
+
+    /// Apply a closure function to the current value and return a guard to the old value.
+    ///
+    /// The closure receives the current value and should return the new value.
+    /// Returns a guard to the old value (before update). The guard is taken while the write
+    /// lock is held, so it holds exactly the value replaced by this call, even under
+    /// concurrent writers.
+    ///
+    /// 对当前值应用闭包函数并返回旧值的守卫。
+    ///
+    /// 闭包接收当前值并应返回新值。
+    /// 返回旧值（更新前）的守卫。守卫在持有写入锁时获取，因此即使存在并发写者，
+    /// 它持有的也正是本次调用替换掉的值。
+    #[inline]
+    #[track_caller]
+    pub fn fetch_and_update<F>(&self, f: F) -> ReadGuard<'_, T>
+    where
+        F: FnOnce(&T) -> T,
+    {
+        let mut swap = self.shared.write_lock();
+        let old_guard = self.read();
+        let new_value = f(&old_guard);
+        self.shared.publish(&mut swap, new_value);
+        old_guard
+    }
+
+    /// Apply a closure function to the current value and transform the result.
+    ///
+    /// This method reads the current value, applies the closure to transform it,
+    /// and returns the transformed result.
+    ///
+    /// 对当前值应用闭包函数并转换结果。
+    ///
+    /// 这个方法读取当前值，应用闭包进行转换，并返回转换后的结果。
+    #[inline]
+    pub fn map<F, U>(&self, f: F) -> U
+    where
+        F: FnOnce(&T) -> U,
+    {
+        let guard = self.read();
+        f(&guard)
+    }
+
+    /// Apply a closure function to the current value, returning Some if the closure returns true.
+    ///
+    /// 对当前值应用闭包函数，如果闭包返回 true 则返回 Some。
+    #[inline]
+    pub fn filter<F>(&self, f: F) -> Option<ReadGuard<'_, T>>
+    where
+        F: FnOnce(&T) -> bool,
+    {
+        let guard = self.read();
+        if f(&guard) { Some(guard) } else { None }
+    }
+
+    /// Get the current value by cloning.
+    ///
+    /// 通过克隆获取当前值。
+    #[inline]
+    pub fn get(&self) -> T
+    where
+        T: Clone,
+    {
+        self.read().cloned()
+    }
+
+    /// Write operation (Guard style) - Requires T to implement Clone
+    ///
+    /// Returns WriteGuard, allowing direct data modification, automatically committed on drop.
+    /// Acquires Mutex lock to ensure serialized writes.
+    /// With `std`, a guard dropped during a panic discards its changes instead.
+    ///
+    /// # Panics
+    ///
+    /// With the `reentrancy-check` feature (on by default), panics if this thread already
+    /// holds the write lock, through any handle of the same lock, instead of deadlocking.
+    /// The same holds for every other blocking write. Without `std`, "this thread" is the
+    /// context identified by `set_context_id`.
+    ///
+    /// 写入操作（Guard 方式）- 需要 T 实现 Clone
+    ///
+    /// 返回 WriteGuard，允许直接修改数据，在 drop 时自动提交。
+    /// 获取 Mutex 锁，确保串行化写入。
+    /// 启用 `std` 时，在 panic 期间被 drop 的守卫会丢弃其修改。
+    ///
+    /// # Panics
+    ///
+    /// 启用 `reentrancy-check` feature（默认开启）时，如果本线程已通过同一个锁的任意句柄持有写入锁，
+    /// 则 panic 而不是死锁。其他所有阻塞写入同样如此。没有 `std` 时，“本线程”指由
+    /// `set_context_id` 标识的上下文。
+    #[inline]
+    #[track_caller]
+    pub fn write(&self) -> WriteGuard<'_, T, R>
+    where
+        T: Clone,
+    {
+        WriteGuard::new(&self.shared, self.shared.write_lock(), OnDrop::Commit)
+    }
+
+    /// Write operation that only commits explicitly - Requires T to implement Clone
+    ///
+    /// Like [`write`](Self::write), but dropping the guard discards the draft; only
+    /// [`WriteGuard::commit`] publishes it. Unlike the unwind detection of `write`, this works
+    /// without `std`.
+    ///
+    /// 仅显式提交的写入操作 - 需要 T 实现 Clone
+    ///
+    /// 与 [`write`](Self::write) 相同，但 drop 守卫会丢弃草稿；只有 [`WriteGuard::commit`]
+    /// 会发布它。与 `write` 的展开检测不同，它在没有 `std` 时也能工作。
+    #[inline]
+    #[track_caller]
+    pub fn write_explicit(&self) -> WriteGuard<'_, T, R>
+    where
+        T: Clone,
+    {
+        WriteGuard::new(&self.shared, self.shared.write_lock(), OnDrop::Discard)
+    }
+
+    /// Asynchronously acquire the write lock - Requires T to implement Clone
+    ///
+    /// Waits in a FIFO queue without blocking the thread, so it is safe to use on async
+    /// executors. Blocking writers queue up in the same order, and nobody overtakes a queued
+    /// waiter. Dropping the future before it completes releases its place in the queue and
+    /// never commits anything.
+    ///
+    /// 异步获取写入锁 - 需要 T 实现 Clone
+    ///
+    /// 在 FIFO 队列中等待而不阻塞线程，因此可以安全地在异步执行器上使用。阻塞的写者按同样的顺序排队，
+    /// 且没有人会超过排队的等待者。在完成前丢弃 future 会释放其在队列中的位置，且不会提交任何内容。
+    #[inline]
+    pub fn write_async(&self) -> WriteFuture<'_, T, R>
+    where
+        T: Clone,
+    {
+        WriteFuture {
+            shared: &self.shared,
+            inner: self.shared.swap.lock_async(),
+        }
+    }
+
+    /// Asynchronously store a new value, making it visible to readers.
+    ///
+    /// 异步存储新值，使其对读者可见。
+    #[inline]
+    pub fn store_async(&self, new_value: T) -> impl Future<Output = ()> {
+        let shared = &self.shared;
+        async move {
+            let mut swap = shared.check_open(shared.guard(shared.swap.lock_async().await));
+            shared.publish(&mut swap, new_value);
+        }
+    }
+
+    /// Asynchronously update the value using a closure.
+    ///
+    /// The closure runs once the write lock has been acquired.
+    ///
+    /// 异步使用闭包更新值。
+    ///
+    /// 闭包在获取写入锁之后运行。
+    #[inline]
+    pub fn update_async<F>(&self, f: F) -> impl Future<Output = ()>
+    where
+        F: FnOnce(&T) -> T,
+    {
+        let shared = &self.shared;
+        async move {
+            let mut swap = shared.check_open(shared.guard(shared.swap.lock_async().await));
+            let new_value = f(&swap.get().value);
+            shared.publish(&mut swap, new_value);
+        }
+    }
+
+    /// Try to acquire write lock
+    ///
+    /// Never blocks: fails with [`TryWriteError::WouldBlock`] if another writer holds or
+    /// waits for the write lock, [`TryWriteError::Reentrant`] if this thread holds it (when
+    /// reentrancy is detected, see [`write`](Self::write)), or [`TryWriteError::Closed`] if the lock is closed.
+    /// `CriticalSectionRawMutex` only keeps this promise with single-core implementations.
+    ///
+    /// 尝试获取写入锁
+    ///
+    /// 永不阻塞：如果另一个写者持有或正在等待写入锁，则以 [`TryWriteError::WouldBlock`] 失败；
+    /// 如果本线程持有写入锁（检测重入时，参见 [`write`](Self::write)），则以 [`TryWriteError::Reentrant`] 失败；
+    /// 如果锁已关闭，则以 [`TryWriteError::Closed`] 失败。
+    /// `CriticalSectionRawMutex` 只有在单核实现上才能保证这一点。
+    #[inline]
+    #[track_caller]
+    pub fn try_write(&self) -> Result<WriteGuard<'_, T, R>, TryWriteError>
+    where
+        T: Clone,
+    {
+        let swap_guard = self.shared.try_write_lock()?;
+        Ok(WriteGuard::new(&self.shared, swap_guard, OnDrop::Commit))
+    }
+
+    /// Acquire the write lock, giving up once `timeout` has passed - Requires T to implement Clone
+    ///
+    /// Fails with [`TryWriteError::Timeout`] if no other writer released the write lock in
+    /// time, or [`TryWriteError::Closed`] if the lock is closed. Waits in the same FIFO queue
+    /// as [`write_async`](Self::write_async), so the thread sleeps until an unlock.
+    ///
+    /// 获取写入锁，超过 `timeout` 后放弃 - 需要 T 实现 Clone
+    ///
+    /// 如果没有其他写者及时释放写入锁，则以 [`TryWriteError::Timeout`] 失败；如果锁已关闭，
+    /// 则以 [`TryWriteError::Closed`] 失败。与 [`write_async`](Self::write_async)
+    /// 在同一个 FIFO 队列中等待，因此线程会休眠直到解锁。
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use lfrlock::{LfrLock, TryWriteError};
+    /// use std::time::Duration;
+    ///
+    /// let config = LfrLock::new(String::from("v1"));
+    /// let admin = config.clone();
+    /// let (locked, wait_locked) = std::sync::mpsc::channel();
+    /// let handle = std::thread::spawn(move || {
+    ///     let mut guard = admin.write();
+    ///     locked.send(()).unwrap();
+    ///     std::thread::sleep(Duration::from_millis(50));
+    ///     guard.push_str("-patched");
+    /// });
+    /// wait_locked.recv().unwrap();
+    ///
+    /// // A request handler gives up instead of hanging
+    /// let err = config.write_timeout(Duration::from_millis(1)).unwrap_err();
+    /// assert_eq!(err, TryWriteError::Timeout);
+    /// handle.join().unwrap();
+    /// assert_eq!(*config.read(), "v1-patched");
+    /// ```
+    #[cfg(feature = "std")]
+    #[inline]
+    #[track_caller]
+    pub fn write_timeout(&self, timeout: Duration) -> Result<WriteGuard<'_, T, R>, TryWriteError>
+    where
+        T: Clone,
+    {
+        let swap_guard = self.shared.write_lock_timeout(timeout)?;
+        Ok(WriteGuard::new(&self.shared, swap_guard, OnDrop::Commit))
+    }
+
+    /// Read data - never blocks
+    ///
+    /// 读取数据 - 永不阻塞
+    #[inline]
+    pub fn read(&self) -> ReadGuard<'_, T> {
+        let inner = self.local.load();
+        let prior = inner.shows_prior();
+        ReadGuard {
+            inner,
+            local: &self.local,
+            prior,
+        }
+    }
+
+    /// Read data, reporting whether a writer panicked - never blocks
+    ///
+    /// Returns `Err` carrying a guard to the last good value if the lock was built with
+    /// poisoning enabled and a writer panicked since the last [`clear_poison`](Self::clear_poison).
+    ///
+    /// 读取数据，并报告是否有写者 panic - 永不阻塞
+    ///
+    /// 如果锁启用了中毒，并且自上次 [`clear_poison`](Self::clear_poison) 以来有写者 panic，
+    /// 则返回携带最后一个正常值守卫的 `Err`。
+    #[inline]
+    pub fn read_checked(&self) -> LockResult<ReadGuard<'_, T>> {
+        let guard = self.read();
+        if self.is_poisoned() {
+            Err(PoisonError::new(guard))
+        } else {
+            Ok(guard)
+        }
+    }
+
+    /// Acquire the write lock, reporting whether a writer panicked - Requires T to implement Clone
+    ///
+    /// Like [`write`](Self::write), but returns `Err` carrying the guard if the lock is
+    /// poisoned. The guard still works, so the caller can repair the value.
+    ///
+    /// 获取写入锁，并报告是否有写者 panic - 需要 T 实现 Clone
+    ///
+    /// 与 [`write`](Self::write) 相同，但如果锁已中毒则返回携带守卫的 `Err`。
+    /// 守卫仍然可用，因此调用者可以修复该值。
+    #[inline]
+    #[track_caller]
+    pub fn write_checked(&self) -> LockResult<WriteGuard<'_, T, R>>
+    where
+        T: Clone,
+    {
+        let guard = self.write();
+        if self.is_poisoned() {
+            Err(PoisonError::new(guard))
+        } else {
+            Ok(guard)
+        }
+    }
+
+    /// Check whether a writer panicked while holding the write lock.
+    ///
+    /// Always `false` unless poisoning was enabled with [`LfrLockBuilder::poisoning`].
+    ///
+    /// 检查是否有写者在持有写入锁时 panic。
+    ///
+    /// 除非通过 [`LfrLockBuilder::poisoning`] 启用了中毒，否则始终为 `false`。
+    #[inline]
+    pub fn is_poisoned(&self) -> bool {
+        self.shared.swap.is_poisoned()
+    }
 
-/// LfrLock (Lock-Free Read Lock) - Reads never block, writes are serialized using Mutex
-///
-/// Similar to `std::sync::Mutex`, a unified type supports both read and write operations.
-/// Core features: Read operations are lock-free and never block; write operations involve copying old data, modifying, and then atomically replacing.
-///
-/// LfrLock (Lock-Free Read Lock) - 读取永不阻塞，写入使用 Mutex 串行化
-///
-/// 类似于 `std::sync::Mutex`，统一的类型同时支持读写操作。
-/// 核心特性：读取操作无锁且永不阻塞；写入操作涉及复制旧数据、修改、然后原子替换。
-pub struct LfrLock<T: 'static> {
-    swap: Arc<Mutex<SmrSwap<T>>>,
-    local: LocalReader<T>,
-}
+    /// Clear the poisoned state.
+    ///
+    /// 清除中毒状态。
+    #[inline]
+    pub fn clear_poison(&self) {
+        self.shared.swap.clear_poison();
+    }
 
-impl<T: 'static> LfrLock<T> {
-    /// Create a new LfrLock
+    /// Stop accepting writes through every handle of this lock.
     ///
-    /// 创建新的 LfrLock
+    /// Waits for a write in progress to finish. Afterwards the `try_*` writes and
+    /// [`write_timeout`](Self::write_timeout) fail with [`TryWriteError::Closed`], and every
+    /// other write panics. Reads keep working. Useful during shutdown, when a late write would
+    /// be lost anyway.
+    ///
+    /// 停止通过此锁的所有句柄接受写入。
+    ///
+    /// 会等待正在进行的写入完成。之后 `try_*` 写入和 [`write_timeout`](Self::write_timeout)
+    /// 会以 [`TryWriteError::Closed`] 失败，其他所有写入都会 panic。读取照常进行。
+    /// 适用于关闭期间，此时迟到的写入无论如何都会丢失。
     #[inline]
-    pub fn new(initial: T) -> Self {
-        let swap = SmrSwap::new(initial);
-        let local = swap.local();
+    #[track_caller]
+    pub fn close(&self) {
+        let _swap = self.shared.lock();
+        self.shared.closed.store(true, Ordering::Relaxed);
+    }
 
-        LfrLock {
-            swap: Arc::new(Mutex::new(swap)),
-            local,
-        }
+    /// Check whether [`close`](Self::close) has been called.
+    ///
+    /// 检查是否已调用 [`close`](Self::close)。
+    #[inline]
+    pub fn is_closed(&self) -> bool {
+        self.shared.closed.load(Ordering::Relaxed)
     }
 
-    /// Store a new value, making it visible to readers.
+    /// Get the version of the current value.
     ///
-    /// The old value is retired and will be garbage collected when safe.
+    /// The version starts at 0 and is incremented by every commit
+    /// (`store`, `update`, `swap`, `WriteGuard` drop, ...).
     ///
-    /// 存储新值，使其对读者可见。
+    /// 获取当前值的版本号。
     ///
-    /// 旧值已退休，将在安全时被垃圾回收。
+    /// 版本号从 0 开始，每次提交（`store`、`update`、`swap`、`WriteGuard` drop 等）都会递增。
     #[inline]
-    pub fn store(&self, new_value: T) {
-        let mut swap = self.swap.lock();
-        swap.store(new_value);
+    pub fn version(&self) -> usize {
+        self.local.map(Slot::visible_version)
     }
 
-    /// Atomically swap the current value with a new one.
+    /// Get the read/write preference this lock was built with.
     ///
-    /// Returns the old value.
+    /// 获取构建此锁时使用的读/写优先策略。
+    #[inline]
+    pub fn strategy(&self) -> Strategy {
+        self.local.strategy()
+    }
+
+    /// Get the strategy readers and writers currently use.
     ///
-    /// 原子地将当前值与新值交换。
+    /// Equals [`strategy`](Self::strategy) unless it is [`Strategy::Adaptive`], in which case
+    /// this tells which of the two is in use right now.
     ///
-    /// 返回旧的值。
+    /// 获取读者和写者当前使用的策略。
+    ///
+    /// 除非为 [`Strategy::Adaptive`]，否则与 [`strategy`](Self::strategy) 相同；
+    /// 对于自适应策略，它表示当前正在使用两者中的哪一个。
     #[inline]
-    pub fn swap(&self, new_value: T) -> T
-    where
-        T: Clone,
-    {
-        self.swap.lock().swap(new_value)
+    pub fn active_strategy(&self) -> Strategy {
+        self.local.active_strategy()
     }
 
-    /// Update the value using a closure.
+    /// Get the number of retired values that are not reclaimed yet.
     ///
-    /// The closure receives the current value and should return the new value.
+    /// Recorded whenever the write lock is released, so reading it never blocks. Includes
+    /// values still visible to a reader, and copies of the current value used internally
+    /// to advance the reclamation epoch.
     ///
-    /// 使用闭包更新值。
+    /// 获取尚未回收的退休值数量。
     ///
-    /// 闭包接收当前值并应返回新值。
+    /// 每次释放写入锁时记录，因此读取它永不阻塞。包括仍对读者可见的值，
+    /// 以及内部用于推进回收纪元的当前值副本。
     #[inline]
-    pub fn update<F>(&self, f: F)
-    where
-        F: FnOnce(&T) -> T,
-    {
-        self.swap.lock().update(f);
+    pub fn backlog(&self) -> usize {
+        self.shared.reclaimer.backlog()
     }
 
-    /// Apply a closure function to the current value and return a guard to the new value.
+    /// Wait for a grace period: return once every reader that could see a value retired
+    /// before this call has dropped its guard, and those values have been dropped.
     ///
-    /// The closure receives a reference to the current value and returns a new value.
+    /// Use it to make sure the destructor of an old value has run, e.g. before continuing a
+    /// shutdown sequence. Values waiting in the drop queue of
+    /// [`deferred_drop`](LfrLockBuilder::deferred_drop) are dropped as well. Values still held
+    /// by a [`Retired`] handle are only dropped with the handle. Must not be called while this thread holds a read guard or the write lock.
     ///
-    /// 对当前值应用闭包函数并返回新值的守卫。
+    /// 等待宽限期：当所有可能看到本次调用之前退休的值的读者都 drop 了其守卫，
+    /// 并且这些值已被 drop 后返回。
     ///
-    /// 闭包接收当前值的引用，返回新值。
+    /// 用于确保旧值的析构函数已经运行，例如在继续关闭流程之前。在
+    /// [`deferred_drop`](LfrLockBuilder::deferred_drop) 的 drop 队列中等待的值也会被 drop。仍被 [`Retired`] 句柄持有的值
+    /// 只会随句柄一起被 drop。不得在本线程持有读取守卫或写入锁时调用。
     #[inline]
-    pub fn update_and_fetch<F>(&self, f: F) -> ReadGuard<'_, T>
-    where
-        F: FnOnce(&T) -> T,
-    {
-        self.swap.lock().update(f);
-        self.local.load()
+    #[track_caller]
+    pub fn synchronize(&self) {
+        self.shared.synchronize_with(|| true);
     }
 
-    /// Apply a closure function to the current value and return a guard to the old value.
+    /// Like [`synchronize`](Self::synchronize), but gives up after `timeout`.
     ///
-    /// The closure receives the current value and should return the new value.
-    /// Returns a guard to the old value (before update).
+    /// Returns `true` if the grace period completed in time.
     ///
-    /// 对当前值应用闭包函数并返回旧值的守卫。
+    /// 与 [`synchronize`](Self::synchronize) 相同，但在 `timeout` 之后放弃。
     ///
-    /// 闭包接收当前值并应返回新值。
-    /// 返回旧值（更新前）的守卫。
+    /// 如果宽限期按时完成则返回 `true`。
+    #[cfg(feature = "std")]
+    #[track_caller]
+    pub fn synchronize_timeout(&self, timeout: Duration) -> bool {
+        let deadline = Instant::now().checked_add(timeout);
+        self.shared
+            .synchronize_with(|| deadline.is_none_or(|deadline| Instant::now() < deadline))
+    }
+
+    /// Reclaim every retired value that no reader can see anymore, right now.
+    ///
+    /// Unlike [`synchronize`](Self::synchronize) this never waits for readers; values still
+    /// in use are left for a later collection.
+    ///
+    /// 立即回收所有读者都不再能看到的退休值。
+    ///
+    /// 与 [`synchronize`](Self::synchronize) 不同，它从不等待读者；仍在使用的值留待之后回收。
     #[inline]
-    pub fn fetch_and_update<F>(&self, f: F) -> ReadGuard<'_, T>
-    where
-        F: FnOnce(&T) -> T,
-    {
-        let old_guard = self.local.load();
-        self.swap.lock().update(f);
-        old_guard
+    #[track_caller]
+    pub fn flush_retired(&self) {
+        let mut swap = self.shared.lock();
+        if swap.garbage_count() > 0 {
+            let epoch = swap.version().wrapping_sub(1);
+            self.shared.reclaim_through(&mut swap, epoch);
+        }
     }
 
-    /// Apply a closure function to the current value and transform the result.
+    /// Set a hook that receives every retired value when it is actually reclaimed.
     ///
-    /// This method reads the current value, applies the closure to transform it,
-    /// and returns the transformed result.
+    /// The hook is called with the version and the owned value once no reader can see it
+    /// anymore, instead of just dropping it, e.g. to return buffers to a pool. It runs
+    /// outside the writer Mutex, on the thread that released the write lock after the
+    /// reclamation. Replaces a previously set hook and is shared by every handle.
+    /// Values taken out through a [`Retired`] handle, and values still held when the lock
+    /// itself is dropped, do not reach the hook.
     ///
-    /// 对当前值应用闭包函数并转换结果。
+    /// 设置一个钩子，在每个退休值真正被回收时接收它。
     ///
-    /// 这个方法读取当前值，应用闭包进行转换，并返回转换后的结果。
+    /// 当没有读者还能看到该值时，钩子会以其版本号和拥有所有权的值被调用，而不是直接 drop 它，
+    /// 例如用于将缓冲区归还到池中。钩子在写入 Mutex 之外、在回收后释放写入锁的线程上运行。
+    /// 会替换之前设置的钩子，并由所有句柄共享。通过 [`Retired`] 句柄取出的值，
+    /// 以及锁本身被 drop 时仍持有的值，不会交给钩子。
     #[inline]
-    pub fn map<F, U>(&self, f: F) -> U
+    pub fn set_reclaim_hook<F>(&self, hook: F)
     where
-        F: FnOnce(&T) -> U,
+        F: Fn(usize, T) + Send + Sync + 'static,
     {
-        let guard = self.local.load();
-        f(&*guard)
+        let hook: Box<dyn Fn(usize, T) + Send + Sync> = Box::new(hook);
+        self.shared.reclaimer.set_hook(Some(Arc::from(hook)));
     }
 
-    /// Apply a closure function to the current value, returning Some if the closure returns true.
+    /// Remove the reclamation hook, retired values are simply dropped again.
     ///
-    /// 对当前值应用闭包函数，如果闭包返回 true 则返回 Some。
+    /// Values already reclaimed but not yet handed to the hook, e.g. by a writer that has
+    /// just released the write lock, are passed to it on the calling thread before this
+    /// returns.
+    ///
+    /// 移除回收钩子，退休值将再次被直接 drop。
+    ///
+    /// 已被回收但尚未交给钩子的值（例如刚释放写入锁的写者回收的值），会在返回之前于调用线程上交给它。
     #[inline]
-    pub fn filter<F>(&self, f: F) -> Option<ReadGuard<'_, T>>
-    where
-        F: FnOnce(&T) -> bool,
-    {
-        let guard = self.local.load();
-        if f(&*guard) { Some(guard) } else { None }
+    pub fn clear_reclaim_hook(&self) {
+        self.shared.reclaimer.set_hook(None);
     }
 
-    /// Get the current value by cloning.
+    /// Drop every retired value waiting in the drop queue, returning how many there were.
     ///
-    /// 通过克隆获取当前值。
+    /// Only does something with [`LfrLockBuilder::deferred_drop`] or
+    /// [`LfrLockBuilder::background_reclaimer`]. The values are dropped on the calling thread,
+    /// outside every lock.
+    ///
+    /// drop 所有在 drop 队列中等待的退休值，返回其数量。
+    ///
+    /// 仅在使用 [`LfrLockBuilder::deferred_drop`] 或 [`LfrLockBuilder::background_reclaimer`]
+    /// 时有效。这些值在调用线程上、在所有锁之外被 drop。
     #[inline]
-    pub fn get(&self) -> T
-    where
-        T: Clone,
-    {
-        (&*self.local.load()).clone()
+    pub fn drain_retired(&self) -> usize {
+        self.shared.reclaimer.drain_queue()
     }
 
-    /// Write operation (Guard style) - Requires T to implement Clone
+    /// Subscribe to changes of this lock.
     ///
-    /// Returns WriteGuard, allowing direct data modification, automatically committed on drop.
-    /// Acquires Mutex lock to ensure serialized writes.
+    /// The returned [`Subscriber`] treats the current value as seen and reports every later
+    /// commit, keeping only the latest value. It hooks into every commit path.
     ///
-    /// 写入操作（Guard 方式）- 需要 T 实现 Clone
+    /// 订阅此锁的变更。
     ///
-    /// 返回 WriteGuard，允许直接修改数据，在 drop 时自动提交。
-    /// 获取 Mutex 锁，确保串行化写入。
+    /// 返回的 [`Subscriber`] 将当前值视为已看到，并报告之后的每次提交，只保留最新值。
+    /// 它会挂接到所有提交路径上。
     #[inline]
-    pub fn write(&self) -> WriteGuard<'_, T>
+    pub fn subscribe(&self) -> Subscriber<T, R> {
+        Subscriber::new(self.clone())
+    }
+
+    /// Block the current thread until the committed value satisfies `pred`.
+    ///
+    /// Returns a guard to the first value found satisfying `pred`. The thread parks between
+    /// commits instead of polling, and readers that never wait pay nothing for this.
+    ///
+    /// 阻塞当前线程，直到已提交的值满足 `pred`。
+    ///
+    /// 返回第一个被发现满足 `pred` 的值的守卫。线程在提交之间挂起而不是轮询，
+    /// 从不等待的读取者不会为此付出任何代价。
+    #[cfg(feature = "std")]
+    pub fn wait_until<F>(&self, pred: F) -> ReadGuard<'_, T>
     where
-        T: Clone,
+        F: FnMut(&T) -> bool,
     {
-        WriteGuard::new(self)
+        self.wait_until_deadline(pred, None)
+            .expect("waiting without a deadline never times out")
     }
 
-    /// Try to acquire write lock
+    /// Like [`wait_until`](Self::wait_until), but gives up after `timeout` and returns `None`.
     ///
-    /// 尝试获取写入锁
-    #[inline]
-    pub fn try_write(&self) -> Option<WriteGuard<'_, T>>
+    /// 与 [`wait_until`](Self::wait_until) 相同，但在 `timeout` 之后放弃并返回 `None`。
+    #[cfg(feature = "std")]
+    pub fn wait_until_timeout<F>(&self, pred: F, timeout: Duration) -> Option<ReadGuard<'_, T>>
     where
-        T: Clone,
+        F: FnMut(&T) -> bool,
     {
-        let swap_guard = self.swap.try_lock().ok()?;
-
-        let guard = self.local.load();
-        let data = (*guard).clone();
+        self.wait_until_deadline(pred, Instant::now().checked_add(timeout))
+    }
 
-        Some(WriteGuard {
-            swap_guard,
-            data: ManuallyDrop::new(data),
+    /// Block the current thread until a version other than `since_version` is committed.
+    ///
+    /// Returns immediately if that already happened.
+    ///
+    /// 阻塞当前线程，直到提交了与 `since_version` 不同的版本。
+    ///
+    /// 如果已经发生则立即返回。
+    #[cfg(feature = "std")]
+    pub fn wait_for_change(&self, since_version: usize) -> ReadGuard<'_, T> {
+        wait::park_until(&[&self.shared.watchers], None, || {
+            let guard = self.read();
+            (guard.version() != since_version).then_some(guard)
         })
+        .expect("waiting without a deadline never times out")
     }
 
-    /// Read data - never blocks
-    ///
-    /// 读取数据 - 永不阻塞
-    #[inline]
-    pub fn read(&self) -> ReadGuard<'_, T> {
-        self.local.load()
+    #[cfg(feature = "std")]
+    fn wait_until_deadline<F>(
+        &self,
+        mut pred: F,
+        deadline: Option<Instant>,
+    ) -> Option<ReadGuard<'_, T>>
+    where
+        F: FnMut(&T) -> bool,
+    {
+        wait::park_until(&[&self.shared.watchers], deadline, || {
+            let guard = self.read();
+            pred(&guard).then_some(guard)
+        })
     }
 
     /// Create a factory for creating new `LfrLock` instances.
@@ -213,21 +1384,21 @@ impl<T: 'static> LfrLock<T> {
     ///
     /// 返回的工厂是 `Sync` + `Clone` 的，可以在线程之间共享。
     #[inline]
-    pub fn factory(&self) -> LfrLockFactory<T> {
+    pub fn factory(&self) -> LfrLockFactory<T, R> {
         LfrLockFactory {
-            swap: self.swap.clone(),
-            reader: self.local.share(),
+            shared: self.shared.clone(),
+            reader: self.local.factory(),
         }
     }
 }
 
-impl<T: Default + 'static> Default for LfrLock<T> {
+impl<T: Default + 'static, R: RawMutex> Default for LfrLock<T, R> {
     /// Create a new LfrLock with the default value.
     ///
     /// 使用默认值创建一个新的 LfrLock。
     #[inline]
     fn default() -> Self {
-        Self::new(T::default())
+        LfrLock::builder().raw_mutex().build(T::default())
     }
 }
 
@@ -241,7 +1412,7 @@ impl<T: 'static> From<T> for LfrLock<T> {
     }
 }
 
-impl<T: fmt::Debug + 'static> fmt::Debug for LfrLock<T> {
+impl<T: fmt::Debug + 'static, R: RawMutex> fmt::Debug for LfrLock<T, R> {
     #[inline]
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         let data = self.read();
@@ -249,12 +1420,12 @@ impl<T: fmt::Debug + 'static> fmt::Debug for LfrLock<T> {
     }
 }
 
-impl<T: 'static> Clone for LfrLock<T> {
+impl<T: 'static, R: RawMutex> Clone for LfrLock<T, R> {
     #[inline]
     fn clone(&self) -> Self {
         Self {
-            swap: self.swap.clone(),
-            local: self.local.clone(),
+            shared: self.shared.clone(),
+            local: register_reader(|| self.local.clone()),
         }
     }
 }
@@ -262,55 +1433,239 @@ impl<T: 'static> Clone for LfrLock<T> {
 /// Write Guard - Provides direct mutable access, automatically commits changes on Drop
 /// Holds Mutex lock to ensure exclusive write access
 ///
+/// The current value is only cloned on the first mutable access, and a guard that was never
+/// mutably accessed commits nothing. If the guard is dropped while the thread is panicking,
+/// the draft is discarded and the old value stays current. Use [`commit`](Self::commit) or
+/// [`abort`](Self::abort) to finish the write explicitly.
+///
 /// 写入保护器 - 提供直接的可变访问，在 Drop 时自动提交更改
 /// 持有 Mutex 锁，确保独占写入访问
-pub struct WriteGuard<'a, T: 'static> {
-    swap_guard: MutexGuard<'a, SmrSwap<T>>,
-    data: ManuallyDrop<T>,
+///
+/// 当前值仅在第一次可变访问时被克隆，从未被可变访问的守卫不会提交任何内容。
+/// 如果守卫在线程 panic 期间被 drop，草稿会被丢弃，旧值保持为当前值。
+/// 使用 [`commit`](Self::commit) 或 [`abort`](Self::abort) 显式结束写入。
+pub struct WriteGuard<'a, T: 'static, R: RawMutex = DefaultRawMutex> {
+    shared: &'a Shared<T, R>,
+    swap_guard: SwapGuard<'a, T, R>,
+    /// Draft cloned on the first mutable access, `None` while untouched.
+    ///
+    /// 在第一次可变访问时克隆的草稿，未被修改时为 `None`。
+    data: Option<T>,
+    on_drop: OnDrop,
 }
 
-impl<'a, T: 'static + Clone> WriteGuard<'a, T> {
-    #[inline]
-    fn new(lock: &'a LfrLock<T>) -> Self {
-        // 获取 Mutex 锁
-        let swap_guard = lock.swap.lock();
-
-        let guard = lock.local.load();
-        let data = (*guard).clone();
+/// What a `WriteGuard` does with its draft when dropped.
+///
+/// `WriteGuard` 在 drop 时如何处理草稿。
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum OnDrop {
+    /// Publish the draft, unless the thread is panicking.
+    ///
+    /// 发布草稿，除非线程正在 panic。
+    Commit,
+    /// Discard the draft.
+    ///
+    /// 丢弃草稿。
+    Discard,
+}
 
+impl<'a, T: 'static, R: RawMutex> WriteGuard<'a, T, R> {
+    #[inline]
+    fn new(shared: &'a Shared<T, R>, swap_guard: SwapGuard<'a, T, R>, on_drop: OnDrop) -> Self {
         WriteGuard {
+            shared,
             swap_guard,
-            data: ManuallyDrop::new(data),
+            data: None,
+            on_drop,
+        }
+    }
+
+    /// Publish the draft now and return the version of the current value.
+    ///
+    /// If the guard was never mutably accessed, nothing is published and the version stays
+    /// the same.
+    ///
+    /// 立即发布草稿，并返回当前值的版本号。
+    ///
+    /// 如果守卫从未被可变访问，则不会发布任何内容，版本号保持不变。
+    #[inline]
+    pub fn commit(mut self) -> usize {
+        match self.data.take() {
+            Some(new_data) => self.shared.publish(&mut self.swap_guard, new_data),
+            None => self.swap_guard.get().version,
         }
     }
+
+    /// Discard the draft, keeping the old value current.
+    ///
+    /// 丢弃草稿，保持旧值为当前值。
+    #[inline]
+    pub fn abort(mut self) {
+        self.data = None;
+    }
 }
 
-impl<'a, T: 'static> Deref for WriteGuard<'a, T> {
+impl<'a, T: 'static, R: RawMutex> Deref for WriteGuard<'a, T, R> {
     type Target = T;
 
     #[inline]
     fn deref(&self) -> &Self::Target {
-        &self.data
+        match &self.data {
+            Some(data) => data,
+            None => &self.swap_guard.get().value,
+        }
     }
 }
 
-impl<'a, T: 'static> DerefMut for WriteGuard<'a, T> {
+impl<'a, T: 'static + Clone, R: RawMutex> DerefMut for WriteGuard<'a, T, R> {
     #[inline]
     fn deref_mut(&mut self) -> &mut Self::Target {
-        &mut self.data
+        // Clone lazily, so read-only use of the guard costs no copy
+        // 延迟克隆，使只读使用守卫时无需复制
+        self.data
+            .get_or_insert_with(|| T::clone(&self.swap_guard.get().value))
+    }
+}
+
+impl<'a, T: fmt::Debug + 'static, R: RawMutex> fmt::Debug for WriteGuard<'a, T, R> {
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("WriteGuard").field("data", &**self).finish()
     }
 }
 
-impl<'a, T: 'static> Drop for WriteGuard<'a, T> {
+impl<'a, T: 'static, R: RawMutex> Drop for WriteGuard<'a, T, R> {
     #[inline]
     fn drop(&mut self) {
-        // Take data from ManuallyDrop
-        // 从 ManuallyDrop 中取出数据
-        let new_data = unsafe { ManuallyDrop::take(&mut self.data) };
+        // Nothing to publish if the value was never mutably accessed
+        // 如果值从未被可变访问，则无需发布
+        let Some(new_data) = self.data.take() else {
+            return;
+        };
+
+        // Never publish a half-modified draft while unwinding
+        // 展开期间绝不发布修改了一半的草稿
+        #[cfg(feature = "std")]
+        let commit = self.on_drop == OnDrop::Commit && !std::thread::panicking();
+        #[cfg(not(feature = "std"))]
+        let commit = self.on_drop == OnDrop::Commit;
+
+        if commit {
+            // Execute state swap
+            // 执行状态切换
+            self.shared.publish(&mut self.swap_guard, new_data);
+        }
+    }
+}
+
+/// Future returned by [`LfrLock::write_async`], resolves to a [`WriteGuard`].
+///
+/// [`LfrLock::write_async`] 返回的 future，完成时得到 [`WriteGuard`]。
+#[must_use = "futures do nothing unless polled"]
+pub struct WriteFuture<'a, T: 'static, R: RawMutex = DefaultRawMutex> {
+    shared: &'a Shared<T, R>,
+    inner: LockFuture<'a, Swap<Slot<T>>, R>,
+}
+
+impl<'a, T: 'static + Clone, R: RawMutex> Future for WriteFuture<'a, T, R> {
+    type Output = WriteGuard<'a, T, R>;
+
+    #[inline]
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let shared = self.shared;
+        Pin::new(&mut self.inner).poll(cx).map(|swap_guard| {
+            let swap_guard = shared.check_open(shared.guard(swap_guard));
+            WriteGuard::new(shared, swap_guard, OnDrop::Commit)
+        })
+    }
+}
+
+/// Read Guard - Keeps the value it was loaded with alive, never blocks writers
+///
+/// Dereferences to the value that was current when the guard was created.
+/// Also carries the version of that value.
+///
+/// 读取保护器 - 保持加载时的值存活，永不阻塞写者
+///
+/// 解引用到创建守卫时的当前值，并携带该值的版本号。
+pub struct ReadGuard<'a, T: 'static> {
+    inner: Pinned<'a, Slot<T>>,
+    local: &'a Local<Slot<T>>,
+    /// Holds the prior value of a slot whose transaction had not committed when loaded.
+    ///
+    /// 持有加载时其事务尚未提交的槽的之前的值。
+    prior: bool,
+}
+
+impl<'a, T: 'static> ReadGuard<'a, T> {
+    /// Get the version of the value held by this guard.
+    ///
+    /// 获取此守卫持有的值的版本号。
+    #[inline]
+    pub fn version(&self) -> usize {
+        match &self.inner.prior {
+            Some(prior) if self.prior => prior.version,
+            _ => self.inner.version,
+        }
+    }
+
+    /// Check whether a newer value has been committed since this guard was created.
+    ///
+    /// 检查自此守卫创建以来是否已提交了更新的值。
+    #[inline]
+    pub fn is_stale(&self) -> bool {
+        self.local.map(Slot::visible_version) != self.version()
+    }
+
+    /// Clone the value held by this guard.
+    ///
+    /// 克隆此守卫持有的值。
+    #[inline]
+    pub fn cloned(&self) -> T
+    where
+        T: Clone,
+    {
+        T::clone(self)
+    }
+}
+
+impl<'a, T: 'static> Deref for ReadGuard<'a, T> {
+    type Target = T;
+
+    #[inline]
+    fn deref(&self) -> &T {
+        match &self.inner.prior {
+            Some(prior) if self.prior => &prior.value,
+            _ => &self.inner.value,
+        }
+    }
+}
+
+impl<'a, T: 'static> AsRef<T> for ReadGuard<'a, T> {
+    #[inline]
+    fn as_ref(&self) -> &T {
+        self
+    }
+}
 
-        // Execute state swap
-        // 执行状态切换
-        self.swap_guard.store(new_data);
+impl<'a, T: 'static> Clone for ReadGuard<'a, T> {
+    #[inline]
+    fn clone(&self) -> Self {
+        ReadGuard {
+            inner: self.inner.clone(),
+            local: self.local,
+            prior: self.prior,
+        }
+    }
+}
+
+impl<'a, T: fmt::Debug + 'static> fmt::Debug for ReadGuard<'a, T> {
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ReadGuard")
+            .field("value", &**self)
+            .field("version", &self.version())
+            .finish()
     }
 }
 
@@ -323,9 +1678,9 @@ impl<'a, T: 'static> Drop for WriteGuard<'a, T> {
 ///
 /// 该工厂是 `Sync` + `Clone` 的，可以在线程之间共享。
 /// 它允许为当前线程创建新的 `LfrLock` 实例。
-pub struct LfrLockFactory<T: 'static> {
-    swap: Arc<Mutex<SmrSwap<T>>>,
-    reader: SmrReader<T>,
+pub struct LfrLockFactory<T: 'static, R: RawMutex = DefaultRawMutex> {
+    shared: Arc<Shared<T, R>>,
+    reader: Factory<Slot<T>>,
 }
 
 impl<T: 'static> LfrLockFactory<T> {
@@ -334,31 +1689,76 @@ impl<T: 'static> LfrLockFactory<T> {
     /// 使用初始值创建一个新工厂。
     #[inline]
     pub fn new(initial: T) -> Self {
-        let swap = SmrSwap::new(initial);
-        let reader = swap.reader();
-        Self {
-            swap: Arc::new(Mutex::new(swap)),
-            reader,
-        }
+        LfrLock::builder().build_factory(initial)
+    }
+
+    /// Create a builder for a factory with optional behaviour, see [`LfrLock::builder`].
+    ///
+    /// 创建用于配置可选行为的工厂构建器，参见 [`LfrLock::builder`]。
+    #[inline]
+    pub fn builder() -> LfrLockBuilder<T> {
+        LfrLockBuilder::new()
+    }
+}
+
+impl<T: 'static, R: RawMutex> LfrLockFactory<T, R> {
+    /// Set a hook that receives every retired value when it is actually reclaimed.
+    ///
+    /// See [`LfrLock::set_reclaim_hook`].
+    ///
+    /// 设置一个钩子，在每个退休值真正被回收时接收它。
+    ///
+    /// 参见 [`LfrLock::set_reclaim_hook`]。
+    #[inline]
+    pub fn set_reclaim_hook<F>(&self, hook: F)
+    where
+        F: Fn(usize, T) + Send + Sync + 'static,
+    {
+        let hook: Box<dyn Fn(usize, T) + Send + Sync> = Box::new(hook);
+        self.shared.reclaimer.set_hook(Some(Arc::from(hook)));
+    }
+
+    /// Remove the reclamation hook.
+    ///
+    /// See [`LfrLock::clear_reclaim_hook`].
+    ///
+    /// 移除回收钩子。
+    ///
+    /// 参见 [`LfrLock::clear_reclaim_hook`]。
+    #[inline]
+    pub fn clear_reclaim_hook(&self) {
+        self.shared.reclaimer.set_hook(None);
+    }
+
+    /// Drop every retired value waiting in the drop queue.
+    ///
+    /// See [`LfrLock::drain_retired`].
+    ///
+    /// drop 所有在 drop 队列中等待的退休值。
+    ///
+    /// 参见 [`LfrLock::drain_retired`]。
+    #[inline]
+    pub fn drain_retired(&self) -> usize {
+        self.shared.reclaimer.drain_queue()
     }
 
     /// Create a new lock instance for the current thread.
     ///
     /// 为当前线程创建一个新的锁实例。
     #[inline]
-    pub fn create(&self) -> LfrLock<T> {
+    pub fn create(&self) -> LfrLock<T, R> {
         LfrLock {
-            swap: self.swap.clone(),
-            local: self.reader.local(),
+            shared: self.shared.clone(),
+            local: register_reader(|| self.reader.local_reader()),
         }
     }
 }
 
-impl<T: 'static> Clone for LfrLockFactory<T> {
+impl<T: 'static, R: RawMutex> Clone for LfrLockFactory<T, R> {
     #[inline]
     fn clone(&self) -> Self {
         Self {
-            swap: self.swap.clone(),
+            shared: self.shared.clone(),
             reader: self.reader.clone(),
         }
     }
@@ -368,6 +1768,16 @@ impl<T: 'static> Clone for LfrLockFactory<T> {
 mod lock_impl {
     use std::ops::{Deref, DerefMut};
 
+    /// Default writer mutex with `std` - `parking_lot::RawMutex`
+    ///
+    /// An uncontended lock or unlock is a single atomic operation, contended writers park
+    /// instead of spinning.
+    ///
+    /// `std` 下的默认写入互斥锁 - `parking_lot::RawMutex`
+    ///
+    /// 无竞争的加锁或解锁只需一次原子操作，发生竞争的写者会挂起而不是自旋。
+    pub type DefaultRawMutex = parking_lot::RawMutex;
+
     /// Like `std::sync::Mutex` except that it does not poison itself.
     pub struct Mutex<T: ?Sized>(std::sync::Mutex<T>);
 
@@ -385,16 +1795,6 @@ mod lock_impl {
         pub fn lock<'a>(&'a self) -> MutexGuard<'a, T> {
             MutexGuard(self.0.lock().unwrap_or_else(|e| e.into_inner()))
         }
-
-        /// Like `std::sync::Mutex::try_lock`.
-        #[inline]
-        pub fn try_lock<'a>(&'a self) -> TryLockResult<MutexGuard<'a, T>> {
-            match self.0.try_lock() {
-                Ok(t) => Ok(MutexGuard(t)),
-                Err(std::sync::TryLockError::Poisoned(e)) => Ok(MutexGuard(e.into_inner())),
-                Err(std::sync::TryLockError::WouldBlock) => Err(TryLockError(())),
-            }
-        }
     }
 
     /// Like `std::sync::MutexGuard`.
@@ -416,26 +1816,32 @@ mod lock_impl {
             self.0.deref_mut()
         }
     }
-
-    /// Like `std::sync::TryLockResult`.
-    pub type TryLockResult<T> = Result<T, TryLockError>;
-
-    /// Like `std::sync::TryLockError`.
-    #[derive(Debug)]
-    pub struct TryLockError(());
 }
 
 #[cfg(not(feature = "std"))]
 mod lock_impl {
     use core::ops::{Deref, DerefMut};
 
-    /// `spin::Mutex` wrapper to match `std::sync::Mutex` API.
-    pub struct Mutex<T: ?Sized>(spin::Mutex<T>);
+    /// Default writer mutex without `std` - `spin::Mutex` as a `lock_api::RawMutex`
+    ///
+    /// 没有 `std` 时的默认写入互斥锁 - 作为 `lock_api::RawMutex` 的 `spin::Mutex`
+    #[cfg(not(feature = "critical-section"))]
+    pub type DefaultRawMutex = spin::Mutex<()>;
+
+    /// Default writer mutex without `std` - Held inside a critical section, so interrupt
+    /// handlers may write
+    ///
+    /// 没有 `std` 时的默认写入互斥锁 - 在临界区内持有，因此中断处理程序也可以写入
+    #[cfg(feature = "critical-section")]
+    pub type DefaultRawMutex = crate::CriticalSectionRawMutex;
+
+    /// Mutex over [`DefaultRawMutex`] to match `std::sync::Mutex` API.
+    pub struct Mutex<T: ?Sized>(lock_api::Mutex<DefaultRawMutex, T>);
 
     impl<T> Mutex<T> {
         #[inline]
         pub fn new(t: T) -> Mutex<T> {
-            Mutex(spin::Mutex::new(t))
+            Mutex(lock_api::Mutex::new(t))
         }
     }
 
@@ -444,39 +1850,24 @@ mod lock_impl {
         pub fn lock(&self) -> MutexGuard<'_, T> {
             MutexGuard(self.0.lock())
         }
-
-        #[inline]
-        pub fn try_lock(&self) -> TryLockResult<MutexGuard<'_, T>> {
-            match self.0.try_lock() {
-                Some(guard) => Ok(MutexGuard(guard)),
-                None => Err(TryLockError(())),
-            }
-        }
     }
 
     #[must_use]
-    pub struct MutexGuard<'a, T: ?Sized + 'a>(spin::MutexGuard<'a, T>);
+    pub struct MutexGuard<'a, T: ?Sized + 'a>(lock_api::MutexGuard<'a, DefaultRawMutex, T>);
 
     impl<'a, T: ?Sized> Deref for MutexGuard<'a, T> {
         type Target = T;
 
         #[inline]
         fn deref(&self) -> &T {
-            &*self.0
+            &self.0
         }
     }
 
     impl<'a, T: ?Sized> DerefMut for MutexGuard<'a, T> {
         #[inline]
         fn deref_mut(&mut self) -> &mut T {
-            &mut *self.0
+            &mut self.0
         }
     }
-
-    pub type TryLockResult<T> = Result<T, TryLockError>;
-
-    #[derive(Debug)]
-    pub struct TryLockError(());
 }
-
-use lock_impl::*;
@@ -0,0 +1,466 @@
+//! Guards projected onto a part of the locked value.
+//!
+//! A read guard keeps its value alive behind an `Arc`, so a reference into it stays valid
+//! however the guard moves. A write guard keeps its draft inline, so mapping it moves the
+//! draft into a box first and moves it back when the mapped guard is done.
+//!
+//! 投影到被锁定值的一部分的守卫。
+//!
+//! 读取守卫通过 `Arc` 保持其值存活，因此无论守卫如何移动，指向其内部的引用都保持有效。
+//! 写入守卫将草稿内联保存，因此映射时先将草稿移入 box，在映射守卫结束时再移回。
+
+use core::fmt;
+use core::marker::PhantomData;
+use core::mem::ManuallyDrop;
+use core::ops::{Deref, DerefMut};
+use core::ptr::{self, NonNull};
+
+use lock_api::RawMutex;
+
+use crate::{DefaultRawMutex, ReadGuard, WriteGuard};
+
+#[cfg(feature = "std")]
+use std::boxed::Box;
+
+#[cfg(not(feature = "std"))]
+use alloc::boxed::Box;
+
+impl<'a, T: 'static> ReadGuard<'a, T> {
+    /// Make a guard over a part of the value, e.g. one field of a config.
+    ///
+    /// An associated function, so that it does not shadow a `map` method of `T`.
+    ///
+    /// 创建只覆盖值的一部分的守卫，例如配置中的一个字段。
+    ///
+    /// 这是关联函数，因此不会遮蔽 `T` 的 `map` 方法。
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use lfrlock::{LfrLock, ReadGuard};
+    ///
+    /// struct Config {
+    ///     name: String,
+    ///     port: u16,
+    /// }
+    ///
+    /// let config = LfrLock::new(Config { name: "api".into(), port: 80 });
+    /// let port = ReadGuard::map(config.read(), |config| &config.port);
+    /// assert_eq!(*port, 80);
+    /// ```
+    #[inline]
+    pub fn map<U: ?Sized, F>(this: Self, f: F) -> MappedReadGuard<'a, T, U>
+    where
+        F: FnOnce(&T) -> &U,
+    {
+        let value = NonNull::from(f(&this));
+        MappedReadGuard { guard: this, value }
+    }
+
+    /// Make a guard over a part of the value, or give the guard back if `f` returns `None`.
+    ///
+    /// 创建只覆盖值的一部分的守卫，如果 `f` 返回 `None` 则交还原守卫。
+    #[inline]
+    pub fn try_map<U: ?Sized, F>(this: Self, f: F) -> Result<MappedReadGuard<'a, T, U>, Self>
+    where
+        F: FnOnce(&T) -> Option<&U>,
+    {
+        match f(&this) {
+            Some(value) => Ok(MappedReadGuard {
+                value: NonNull::from(value),
+                guard: this,
+            }),
+            None => Err(this),
+        }
+    }
+}
+
+/// Read guard over a part of a value, made by [`ReadGuard::map`].
+///
+/// Keeps the whole value alive and never blocks writers, like the guard it was made from.
+///
+/// 覆盖值的一部分的读取守卫，由 [`ReadGuard::map`] 创建。
+///
+/// 与创建它的守卫一样，保持整个值存活且永不阻塞写者。
+pub struct MappedReadGuard<'a, T: 'static, U: ?Sized> {
+    guard: ReadGuard<'a, T>,
+    /// Points into the value kept alive by `guard`.
+    ///
+    /// 指向由 `guard` 保持存活的值的内部。
+    value: NonNull<U>,
+}
+
+// SAFETY: Only hands out `&U`, the rest is up to the guard
+// SAFETY: 只交出 `&U`，其余取决于守卫
+unsafe impl<'a, T: 'static, U: ?Sized + Sync> Send for MappedReadGuard<'a, T, U> where
+    ReadGuard<'a, T>: Send
+{
+}
+
+// SAFETY: Only hands out `&U`, the rest is up to the guard
+// SAFETY: 只交出 `&U`，其余取决于守卫
+unsafe impl<'a, T: 'static, U: ?Sized + Sync> Sync for MappedReadGuard<'a, T, U> where
+    ReadGuard<'a, T>: Sync
+{
+}
+
+impl<'a, T: 'static, U: ?Sized> MappedReadGuard<'a, T, U> {
+    /// Get the version of the value this guard is a part of.
+    ///
+    /// 获取此守卫所属的值的版本号。
+    #[inline]
+    pub fn version(&self) -> usize {
+        self.guard.version()
+    }
+
+    /// Check whether a newer value has been committed since this guard was created.
+    ///
+    /// 检查自此守卫创建以来是否已提交了更新的值。
+    #[inline]
+    pub fn is_stale(&self) -> bool {
+        self.guard.is_stale()
+    }
+
+    /// Narrow the guard further, see [`ReadGuard::map`].
+    ///
+    /// 进一步缩小守卫的范围，参见 [`ReadGuard::map`]。
+    #[inline]
+    pub fn map<V: ?Sized, F>(this: Self, f: F) -> MappedReadGuard<'a, T, V>
+    where
+        F: FnOnce(&U) -> &V,
+    {
+        let value = NonNull::from(f(&this));
+        MappedReadGuard {
+            guard: this.guard,
+            value,
+        }
+    }
+
+    /// Narrow the guard further, or give it back if `f` returns `None`.
+    ///
+    /// 进一步缩小守卫的范围，如果 `f` 返回 `None` 则交还原守卫。
+    #[inline]
+    pub fn try_map<V: ?Sized, F>(this: Self, f: F) -> Result<MappedReadGuard<'a, T, V>, Self>
+    where
+        F: FnOnce(&U) -> Option<&V>,
+    {
+        match f(&this) {
+            Some(value) => Ok(MappedReadGuard {
+                value: NonNull::from(value),
+                guard: this.guard,
+            }),
+            None => Err(this),
+        }
+    }
+}
+
+impl<'a, T: 'static, U: ?Sized> Deref for MappedReadGuard<'a, T, U> {
+    type Target = U;
+
+    #[inline]
+    fn deref(&self) -> &U {
+        // SAFETY: The value lives behind an `Arc` that `guard` keeps alive
+        // SAFETY: 值位于由 `guard` 保持存活的 `Arc` 之后
+        unsafe { self.value.as_ref() }
+    }
+}
+
+impl<'a, T: 'static, U: ?Sized> AsRef<U> for MappedReadGuard<'a, T, U> {
+    #[inline]
+    fn as_ref(&self) -> &U {
+        self
+    }
+}
+
+impl<'a, T: 'static, U: ?Sized> Clone for MappedReadGuard<'a, T, U> {
+    #[inline]
+    fn clone(&self) -> Self {
+        MappedReadGuard {
+            guard: self.guard.clone(),
+            value: self.value,
+        }
+    }
+}
+
+impl<'a, T: 'static, U: ?Sized + fmt::Debug> fmt::Debug for MappedReadGuard<'a, T, U> {
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("MappedReadGuard")
+            .field("value", &&**self)
+            .field("version", &self.version())
+            .finish()
+    }
+}
+
+impl<'a, T: 'static + Clone, R: RawMutex> WriteGuard<'a, T, R> {
+    /// Make a guard over a part of the draft, e.g. one field of a config.
+    ///
+    /// `f` gets mutable access, so the current value is cloned right away and the draft is
+    /// committed on drop like any mutably accessed one. An associated function, so that it
+    /// does not shadow a `map` method of `T`.
+    ///
+    /// 创建只覆盖草稿的一部分的守卫，例如配置中的一个字段。
+    ///
+    /// `f` 获得可变访问，因此会立即克隆当前值，并且草稿与任何被可变访问过的草稿一样在 drop 时提交。
+    /// 这是关联函数，因此不会遮蔽 `T` 的 `map` 方法。
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use lfrlock::{LfrLock, WriteGuard};
+    ///
+    /// #[derive(Clone)]
+    /// struct Config {
+    ///     name: String,
+    ///     port: u16,
+    /// }
+    ///
+    /// let config = LfrLock::new(Config { name: "api".into(), port: 80 });
+    /// let mut port = WriteGuard::map(config.write(), |config| &mut config.port);
+    /// *port = 8080;
+    /// drop(port);
+    /// assert_eq!(config.read().port, 8080);
+    /// ```
+    #[inline]
+    pub fn map<U: ?Sized, F>(this: Self, f: F) -> MappedWriteGuard<'a, T, U, R>
+    where
+        F: FnOnce(&mut T) -> &mut U,
+    {
+        match Self::try_map(this, |value| Some(f(value))) {
+            Ok(mapped) => mapped,
+            Err(_) => unreachable!("the projection always succeeds"),
+        }
+    }
+
+    /// Make a guard over a part of the draft, or give the guard back if `f` returns `None`.
+    ///
+    /// 创建只覆盖草稿的一部分的守卫，如果 `f` 返回 `None` 则交还原守卫。
+    #[inline]
+    pub fn try_map<U: ?Sized, F>(
+        mut this: Self,
+        f: F,
+    ) -> Result<MappedWriteGuard<'a, T, U, R>, Self>
+    where
+        F: FnOnce(&mut T) -> Option<&mut U>,
+    {
+        let draft = this
+            .data
+            .take()
+            .unwrap_or_else(|| T::clone(&this.swap_guard.get().value));
+        let mut draft = Draft::new(draft);
+
+        // SAFETY: The draft is only reached through `value` from now on
+        // SAFETY: 从现在起只通过 `value` 访问草稿
+        match f(unsafe { draft.0.as_mut() }) {
+            Some(value) => Ok(MappedWriteGuard {
+                value: NonNull::from(value),
+                guard: this,
+                draft: Some(draft),
+                _marker: PhantomData,
+            }),
+            None => {
+                this.data = Some(draft.into_inner());
+                Err(this)
+            }
+        }
+    }
+}
+
+/// A draft moved into a box, so that references into it survive moving the guard.
+///
+/// 移入 box 的草稿，使指向其内部的引用在守卫移动后仍然有效。
+struct Draft<T>(NonNull<T>);
+
+impl<T> Draft<T> {
+    #[inline]
+    fn new(value: T) -> Self {
+        Draft(NonNull::from(Box::leak(Box::new(value))))
+    }
+
+    #[inline]
+    fn into_inner(self) -> T {
+        let this = ManuallyDrop::new(self);
+        // SAFETY: Made by `Box::leak` and not freed, since `Drop` did not run
+        // SAFETY: 由 `Box::leak` 创建且未被释放，因为 `Drop` 没有运行
+        *unsafe { Box::from_raw(this.0.as_ptr()) }
+    }
+}
+
+impl<T> Drop for Draft<T> {
+    #[inline]
+    fn drop(&mut self) {
+        // SAFETY: Made by `Box::leak` and freed only here
+        // SAFETY: 由 `Box::leak` 创建，且只在此处释放
+        drop(unsafe { Box::from_raw(self.0.as_ptr()) });
+    }
+}
+
+/// Write guard over a part of a draft, made by [`WriteGuard::map`].
+///
+/// Holds the writer lock of the whole value and commits the whole draft on drop, like the
+/// guard it was made from.
+///
+/// 覆盖草稿的一部分的写入守卫，由 [`WriteGuard::map`] 创建。
+///
+/// 与创建它的守卫一样，持有整个值的写入锁，并在 drop 时提交整个草稿。
+pub struct MappedWriteGuard<'a, T: 'static, U: ?Sized, R: RawMutex = DefaultRawMutex> {
+    /// Has no draft of its own while mapped.
+    ///
+    /// 映射期间自身没有草稿。
+    guard: WriteGuard<'a, T, R>,
+    /// Only `None` once handed back to `guard`.
+    ///
+    /// 仅在交还给 `guard` 之后为 `None`。
+    draft: Option<Draft<T>>,
+    /// Points into `draft`.
+    ///
+    /// 指向 `draft` 的内部。
+    value: NonNull<U>,
+    _marker: PhantomData<&'a mut U>,
+}
+
+// SAFETY: Owns the draft `T` and hands out `&mut U`, the rest is up to the guard
+// SAFETY: 拥有草稿 `T` 并交出 `&mut U`，其余取决于守卫
+unsafe impl<'a, T: 'static + Send, U: ?Sized + Send, R: RawMutex> Send
+    for MappedWriteGuard<'a, T, U, R>
+where
+    WriteGuard<'a, T, R>: Send,
+{
+}
+
+// SAFETY: Only hands out `&U` through a shared reference, the rest is up to the guard
+// SAFETY: 通过共享引用只交出 `&U`，其余取决于守卫
+unsafe impl<'a, T: 'static + Sync, U: ?Sized + Sync, R: RawMutex> Sync
+    for MappedWriteGuard<'a, T, U, R>
+where
+    WriteGuard<'a, T, R>: Sync,
+{
+}
+
+impl<'a, T: 'static, U: ?Sized, R: RawMutex> MappedWriteGuard<'a, T, U, R> {
+    /// Hand the draft back to the guard.
+    ///
+    /// 将草稿交还给守卫。
+    #[inline]
+    fn restore(&mut self) {
+        if let Some(draft) = self.draft.take() {
+            self.guard.data = Some(draft.into_inner());
+        }
+    }
+
+    /// Give up the mapping and get the original guard back.
+    ///
+    /// 放弃映射，取回原守卫。
+    #[inline]
+    fn unmap(self) -> WriteGuard<'a, T, R> {
+        let mut this = ManuallyDrop::new(self);
+        this.restore();
+        // SAFETY: `this` is never used or dropped again
+        // SAFETY: `this` 不会再被使用或 drop
+        unsafe { ptr::read(&this.guard) }
+    }
+
+    /// Publish the whole draft now and return the version of the current value.
+    ///
+    /// See [`WriteGuard::commit`].
+    ///
+    /// 立即发布整个草稿，并返回当前值的版本号。
+    ///
+    /// 参见 [`WriteGuard::commit`]。
+    #[inline]
+    pub fn commit(self) -> usize {
+        self.unmap().commit()
+    }
+
+    /// Discard the whole draft, keeping the old value current.
+    ///
+    /// 丢弃整个草稿，保持旧值为当前值。
+    #[inline]
+    pub fn abort(self) {
+        self.unmap().abort()
+    }
+
+    /// Narrow the guard further, see [`WriteGuard::map`].
+    ///
+    /// 进一步缩小守卫的范围，参见 [`WriteGuard::map`]。
+    #[inline]
+    pub fn map<V: ?Sized, F>(this: Self, f: F) -> MappedWriteGuard<'a, T, V, R>
+    where
+        F: FnOnce(&mut U) -> &mut V,
+    {
+        match Self::try_map(this, |value| Some(f(value))) {
+            Ok(mapped) => mapped,
+            Err(_) => unreachable!("the projection always succeeds"),
+        }
+    }
+
+    /// Narrow the guard further, or give it back if `f` returns `None`.
+    ///
+    /// 进一步缩小守卫的范围，如果 `f` 返回 `None` 则交还原守卫。
+    #[inline]
+    pub fn try_map<V: ?Sized, F>(
+        mut this: Self,
+        f: F,
+    ) -> Result<MappedWriteGuard<'a, T, V, R>, Self>
+    where
+        F: FnOnce(&mut U) -> Option<&mut V>,
+    {
+        // SAFETY: The draft is only reached through `value`
+        // SAFETY: 只通过 `value` 访问草稿
+        let Some(value) = f(unsafe { this.value.as_mut() }) else {
+            return Err(this);
+        };
+        let value = NonNull::from(value);
+        let mut this = ManuallyDrop::new(this);
+        // SAFETY: Every field is moved out exactly once, and `this` is never dropped
+        // SAFETY: 每个字段恰好被移出一次，且 `this` 永远不会被 drop
+        unsafe {
+            Ok(MappedWriteGuard {
+                guard: ptr::read(&this.guard),
+                draft: this.draft.take(),
+                value,
+                _marker: PhantomData,
+            })
+        }
+    }
+}
+
+impl<'a, T: 'static, U: ?Sized, R: RawMutex> Deref for MappedWriteGuard<'a, T, U, R> {
+    type Target = U;
+
+    #[inline]
+    fn deref(&self) -> &U {
+        // SAFETY: Points into the boxed draft, owned until `restore`
+        // SAFETY: 指向 box 中的草稿，在 `restore` 之前一直被持有
+        unsafe { self.value.as_ref() }
+    }
+}
+
+impl<'a, T: 'static, U: ?Sized, R: RawMutex> DerefMut for MappedWriteGuard<'a, T, U, R> {
+    #[inline]
+    fn deref_mut(&mut self) -> &mut U {
+        // SAFETY: Points into the boxed draft, owned until `restore`
+        // SAFETY: 指向 box 中的草稿，在 `restore` 之前一直被持有
+        unsafe { self.value.as_mut() }
+    }
+}
+
+impl<'a, T: 'static, U: ?Sized + fmt::Debug, R: RawMutex> fmt::Debug
+    for MappedWriteGuard<'a, T, U, R>
+{
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("MappedWriteGuard")
+            .field("data", &&**self)
+            .finish()
+    }
+}
+
+impl<'a, T: 'static, U: ?Sized, R: RawMutex> Drop for MappedWriteGuard<'a, T, U, R> {
+    #[inline]
+    fn drop(&mut self) {
+        // The guard then commits or discards the draft as usual
+        // 之后由守卫照常提交或丢弃草稿
+        self.restore();
+    }
+}
@@ -0,0 +1,67 @@
+//! Error type returned by the checked accessors of a poisoning lock.
+//!
+//! 中毒锁的检查型访问方法返回的错误类型。
+
+use core::fmt;
+
+/// A type alias for the result of a checked lock access.
+///
+/// 检查型锁访问结果的类型别名。
+pub type LockResult<G> = Result<G, PoisonError<G>>;
+
+/// Error returned when a previous writer panicked while holding the write lock.
+///
+/// A panicking writer never publishes its draft, so the guard carried by this error still
+/// gives access to the last good value.
+///
+/// 当之前的写者在持有写入锁时 panic 时返回的错误。
+///
+/// panic 的写者永远不会发布其草稿，因此该错误携带的守卫仍然可以访问最后一个正常值。
+pub struct PoisonError<G> {
+    guard: G,
+}
+
+impl<G> PoisonError<G> {
+    #[inline]
+    pub(crate) fn new(guard: G) -> Self {
+        PoisonError { guard }
+    }
+
+    /// Consume the error and return the guard to the last good value.
+    ///
+    /// 消耗该错误并返回最后一个正常值的守卫。
+    #[inline]
+    pub fn into_inner(self) -> G {
+        self.guard
+    }
+
+    /// Get a reference to the guard carried by this error.
+    ///
+    /// 获取该错误携带的守卫的引用。
+    #[inline]
+    pub fn get_ref(&self) -> &G {
+        &self.guard
+    }
+
+    /// Get a mutable reference to the guard carried by this error.
+    ///
+    /// 获取该错误携带的守卫的可变引用。
+    #[inline]
+    pub fn get_mut(&mut self) -> &mut G {
+        &mut self.guard
+    }
+}
+
+impl<G> fmt::Debug for PoisonError<G> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("PoisonError").finish_non_exhaustive()
+    }
+}
+
+impl<G> fmt::Display for PoisonError<G> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("a writer panicked while holding the write lock")
+    }
+}
+
+impl<G> core::error::Error for PoisonError<G> {}
@@ -0,0 +1,376 @@
+//! Reclamation hooks.
+//!
+//! smr-swap drops retired slots while the writer Mutex is held. When a hook is set, a dropped
+//! slot only moves its value to a pending list; the value is handed to the hook once the
+//! writer Mutex has been released, so a slow hook never blocks other writers.
+//!
+//! Without a hook, retired values can instead be queued in a bounded `DropQueue` and dropped
+//! by a background thread or a user-driven drain, off the writer's hot path.
+//!
+//! 回收钩子。
+//!
+//! smr-swap 在持有写入 Mutex 时 drop 退休的槽。设置了钩子时，被 drop 的槽只会将其值移入待处理列表；
+//! 在写入 Mutex 释放之后，值才会被交给钩子，因此缓慢的钩子永远不会阻塞其他写者。
+//!
+//! 没有钩子时，退休的值也可以放入有界的 `DropQueue`，由后台线程或用户驱动的清理来 drop，
+//! 从而离开写者的热路径。
+
+use core::mem::ManuallyDrop;
+use core::ops::{Deref, DerefMut};
+
+#[cfg(feature = "std")]
+use std::{collections::VecDeque, thread::Thread, vec::Vec};
+
+#[cfg(not(feature = "std"))]
+use alloc::{collections::VecDeque, vec::Vec};
+
+use lock_api::RawMutex;
+
+use crate::Slot;
+use crate::lock_impl::Mutex;
+use crate::strategy::Swap;
+use crate::sync::{Arc, AtomicBool, AtomicUsize, Ordering};
+use crate::write_lock::WriteLockGuard;
+
+/// Hook called with the version and the value of every reclaimed value.
+///
+/// 对每个被回收的值，以其版本号和值调用的钩子。
+pub(crate) type ReclaimHook<T> = Arc<dyn Fn(usize, T) + Send + Sync>;
+
+/// Collects reclaimed values and hands them to the hook outside the writer Mutex.
+///
+/// 收集被回收的值，并在写入 Mutex 之外将其交给钩子。
+pub(crate) struct Reclaimer<T> {
+    /// Whether a hook is set, lets slots skip the pending list otherwise.
+    ///
+    /// 是否设置了钩子，未设置时槽可以跳过待处理列表。
+    enabled: AtomicBool,
+    hook: Mutex<Option<ReclaimHook<T>>>,
+    pending: Mutex<Vec<(usize, Arc<T>)>>,
+    /// Queue that takes retired values while no hook is set.
+    ///
+    /// 未设置钩子时接收退休值的队列。
+    queue: Option<Arc<DropQueue<T>>>,
+    /// Retired values not reclaimed yet, as of the last unlock of the writer Mutex.
+    ///
+    /// 截至上次释放写入 Mutex 时尚未回收的退休值数量。
+    backlog: AtomicUsize,
+}
+
+impl<T> Reclaimer<T> {
+    #[inline]
+    pub(crate) fn new(queue: Option<Arc<DropQueue<T>>>) -> Self {
+        Reclaimer {
+            enabled: AtomicBool::new(false),
+            hook: Mutex::new(None),
+            pending: Mutex::new(Vec::new()),
+            queue,
+            backlog: AtomicUsize::new(0),
+        }
+    }
+
+    /// Get the number of retired values recorded at the last unlock.
+    ///
+    /// 获取上次解锁时记录的退休值数量。
+    #[inline]
+    pub(crate) fn backlog(&self) -> usize {
+        self.backlog.load(Ordering::Relaxed)
+    }
+
+    /// Set or remove the hook.
+    ///
+    /// Removing it hands the values still pending to the old hook, since `drain` skips them
+    /// once no hook is set.
+    ///
+    /// 设置或移除钩子。
+    ///
+    /// 移除时会将仍在等待的值交给旧钩子，因为未设置钩子时 `drain` 会跳过它们。
+    pub(crate) fn set_hook(&self, hook: Option<ReclaimHook<T>>) {
+        let mut current = self.hook.lock();
+        let pending = {
+            // Switched under the pending lock, so `retire` cannot add a value after the take
+            // 在待处理列表的锁下切换，使 `retire` 无法在取出之后再添加值
+            let mut pending = self.pending.lock();
+            self.enabled.store(hook.is_some(), Ordering::Release);
+            if hook.is_none() {
+                core::mem::take(&mut *pending)
+            } else {
+                Vec::new()
+            }
+        };
+        let old = core::mem::replace(&mut *current, hook);
+        drop(current);
+
+        if let Some(old) = old {
+            for (version, value) in pending {
+                if let Ok(value) = Arc::try_unwrap(value) {
+                    old(version, value);
+                }
+            }
+        }
+    }
+
+    /// Called when a slot is dropped by smr-swap.
+    ///
+    /// 在槽被 smr-swap drop 时调用。
+    #[inline]
+    pub(crate) fn retire(&self, version: usize, value: Arc<T>) {
+        if self.enabled.load(Ordering::Acquire) {
+            // Checked again under the lock, the hook may have been removed in between
+            // 在锁下再次检查，钩子可能在此期间被移除
+            let mut pending = self.pending.lock();
+            if self.enabled.load(Ordering::Relaxed) {
+                pending.push((version, value));
+                return;
+            }
+        }
+        if let Some(queue) = &self.queue
+            // A value shared with a `Retired` handle belongs to the handle, only the last
+            // reference is worth queueing
+            // 与 `Retired` 句柄共享的值归该句柄所有，只有最后一个引用才值得入队
+            && let Some(value) = Arc::into_inner(value)
+        {
+            queue.push(value);
+        }
+    }
+
+    /// Drop every value waiting in the drop queue, returning how many were queued.
+    ///
+    /// drop 所有在 drop 队列中等待的值，返回排队的数量。
+    #[inline]
+    pub(crate) fn drain_queue(&self) -> usize {
+        self.queue.as_ref().map_or(0, |queue| queue.drain())
+    }
+
+    /// Close the drop queue once the lock is gone.
+    ///
+    /// Values retired afterwards are dropped inline, and the background thread exits after
+    /// draining what is left.
+    ///
+    /// 在锁销毁时关闭 drop 队列。
+    ///
+    /// 之后退休的值会被直接 drop，后台线程在清理剩余的值后退出。
+    #[inline]
+    pub(crate) fn close(&self) {
+        if let Some(queue) = &self.queue {
+            queue.close();
+        }
+    }
+
+    /// Hand every pending value to the hook.
+    ///
+    /// Must be called without the writer Mutex held. A value still shared with a slot that
+    /// is not reclaimed yet is skipped here and passed on once that slot is reclaimed. A
+    /// value shared with a `Retired` handle belongs to the handle.
+    ///
+    /// 将所有待处理的值交给钩子。
+    ///
+    /// 必须在未持有写入 Mutex 时调用。仍与尚未回收的槽共享的值会在这里被跳过，
+    /// 并在该槽被回收时再处理。与 `Retired` 句柄共享的值归该句柄所有。
+    pub(crate) fn drain(&self) {
+        if !self.enabled.load(Ordering::Acquire) {
+            return;
+        }
+        let pending = core::mem::take(&mut *self.pending.lock());
+        if pending.is_empty() {
+            return;
+        }
+
+        // Clone the hook out, so it may itself write to the lock
+        // 将钩子克隆出来，使其自身也可以写入该锁
+        let hook = self.hook.lock().clone();
+        for (version, value) in pending {
+            if let (Ok(value), Some(hook)) = (Arc::try_unwrap(value), &hook) {
+                hook(version, value);
+            }
+        }
+    }
+}
+
+/// What happens to a retired value when the drop queue is full.
+///
+/// 当 drop 队列已满时如何处理退休的值。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OverflowPolicy {
+    /// Drop the value on the thread that retired it, as without a queue.
+    ///
+    /// 在使其退休的线程上 drop 该值，与没有队列时相同。
+    DropInline,
+    /// Wait until the queue has been drained enough to take the value.
+    ///
+    /// Without a background thread, something else must call `drain_retired` meanwhile.
+    ///
+    /// 等待直到队列被清理出足够的空间来接收该值。
+    ///
+    /// 没有后台线程时，必须有其他地方同时调用 `drain_retired`。
+    Block,
+}
+
+/// Bounded queue of retired values waiting to be dropped.
+///
+/// 等待被 drop 的退休值的有界队列。
+pub(crate) struct DropQueue<T> {
+    capacity: usize,
+    policy: OverflowPolicy,
+    values: Mutex<VecDeque<T>>,
+    /// Held while drained values are dropped, so a drain waits for one in progress.
+    ///
+    /// 在 drop 已取出的值期间持有，使清理会等待正在进行的清理。
+    dropping: Mutex<()>,
+    /// Set once the lock is gone, tells the background thread to exit.
+    ///
+    /// 锁销毁后设置，通知后台线程退出。
+    closed: AtomicBool,
+    /// Background thread to unpark on every push, if any.
+    ///
+    /// 每次入队时需要唤醒的后台线程（如果有）。
+    #[cfg(feature = "std")]
+    thread: Mutex<Option<Thread>>,
+}
+
+impl<T> DropQueue<T> {
+    #[inline]
+    pub(crate) fn new(capacity: usize, policy: OverflowPolicy) -> Self {
+        DropQueue {
+            capacity,
+            policy,
+            values: Mutex::new(VecDeque::new()),
+            dropping: Mutex::new(()),
+            closed: AtomicBool::new(false),
+            #[cfg(feature = "std")]
+            thread: Mutex::new(None),
+        }
+    }
+
+    /// Queue a value, applying the overflow policy if the queue is full.
+    ///
+    /// 将值入队，如果队列已满则应用溢出策略。
+    fn push(&self, value: T) {
+        loop {
+            if self.closed.load(Ordering::Acquire) {
+                return;
+            }
+            {
+                let mut values = self.values.lock();
+                if values.len() < self.capacity {
+                    values.push_back(value);
+                    drop(values);
+                    self.wake();
+                    return;
+                }
+            }
+
+            match self.policy {
+                OverflowPolicy::DropInline => return,
+                OverflowPolicy::Block => {
+                    self.wake();
+                    #[cfg(feature = "std")]
+                    std::thread::yield_now();
+                    #[cfg(not(feature = "std"))]
+                    core::hint::spin_loop();
+                }
+            }
+        }
+    }
+
+    /// Drop every queued value outside the queue lock, returning how many there were.
+    ///
+    /// Returns only once values taken by a concurrent drain have been dropped as well.
+    ///
+    /// 在队列锁之外 drop 所有排队的值，返回其数量。
+    ///
+    /// 只有在并发清理取走的值也已被 drop 之后才返回。
+    pub(crate) fn drain(&self) -> usize {
+        let _dropping = self.dropping.lock();
+        let values = core::mem::take(&mut *self.values.lock());
+        values.len()
+    }
+
+    #[inline]
+    fn close(&self) {
+        self.closed.store(true, Ordering::Release);
+        self.wake();
+    }
+
+    #[inline]
+    fn wake(&self) {
+        #[cfg(feature = "std")]
+        if let Some(thread) = &*self.thread.lock() {
+            thread.unpark();
+        }
+    }
+}
+
+/// Spawn the background thread that drops queued values.
+///
+/// 启动 drop 排队值的后台线程。
+#[cfg(feature = "std")]
+pub(crate) fn spawn_reclaimer<T: Send + Sync + 'static>(queue: &Arc<DropQueue<T>>) {
+    let queue = queue.clone();
+    std::thread::Builder::new()
+        .name("lfrlock-reclaimer".into())
+        .spawn(move || {
+            // Values pushed before registering are picked up by the first drain
+            // 注册之前入队的值会被第一次清理处理
+            *queue.thread.lock() = Some(std::thread::current());
+            loop {
+                let closed = queue.closed.load(Ordering::Acquire);
+                queue.drain();
+                if closed {
+                    return;
+                }
+                std::thread::park();
+            }
+        })
+        .expect("failed to spawn the reclaimer thread");
+}
+
+/// Writer Mutex guard that drains the reclaimer after unlocking.
+///
+/// 在解锁后处理回收器的写入 Mutex 守卫。
+pub(crate) struct SwapGuard<'a, T: 'static, R: RawMutex> {
+    guard: ManuallyDrop<WriteLockGuard<'a, Swap<Slot<T>>, R>>,
+    reclaimer: &'a Reclaimer<T>,
+}
+
+impl<'a, T: 'static, R: RawMutex> SwapGuard<'a, T, R> {
+    #[inline]
+    pub(crate) fn new(
+        guard: WriteLockGuard<'a, Swap<Slot<T>>, R>,
+        reclaimer: &'a Reclaimer<T>,
+    ) -> Self {
+        SwapGuard {
+            guard: ManuallyDrop::new(guard),
+            reclaimer,
+        }
+    }
+}
+
+impl<'a, T: 'static, R: RawMutex> Deref for SwapGuard<'a, T, R> {
+    type Target = Swap<Slot<T>>;
+
+    #[inline]
+    fn deref(&self) -> &Self::Target {
+        &self.guard
+    }
+}
+
+impl<'a, T: 'static, R: RawMutex> DerefMut for SwapGuard<'a, T, R> {
+    #[inline]
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.guard
+    }
+}
+
+impl<'a, T: 'static, R: RawMutex> Drop for SwapGuard<'a, T, R> {
+    #[inline]
+    fn drop(&mut self) {
+        self.reclaimer
+            .backlog
+            .store(self.guard.garbage_count(), Ordering::Relaxed);
+        // Unlock first, the hook must run outside the writer Mutex
+        // 先解锁，钩子必须在写入 Mutex 之外运行
+        unsafe { ManuallyDrop::drop(&mut self.guard) };
+        self.reclaimer.drain();
+    }
+}
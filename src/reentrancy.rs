@@ -0,0 +1,80 @@
+//! Context identity for the `reentrancy-check` feature.
+//!
+//! A writer mutex records the context that holds it, so that a blocking write from that same
+//! context can panic instead of deadlocking. With `std`, the context is the current thread.
+//! Without `std` there is no thread to ask, so the identity comes from the function given to
+//! [`set_context_id`], e.g. one that combines the core number with the active interrupt.
+//!
+//! `reentrancy-check` feature 的上下文标识。
+//!
+//! 写入互斥锁记录持有它的上下文，使同一上下文中的阻塞写入 panic 而不是死锁。在 `std` 下，
+//! 上下文就是当前线程。没有 `std` 时没有可查询的线程，因此标识来自传给 [`set_context_id`]
+//! 的函数，例如一个组合核心编号和当前活动中断的函数。
+
+use crate::sync::{AtomicPtr, Ordering};
+
+/// Function registered with [`set_context_id`], null while none is.
+///
+/// 通过 [`set_context_id`] 注册的函数，未注册时为空。
+static CONTEXT_ID: AtomicPtr<()> = AtomicPtr::new(core::ptr::null_mut());
+
+/// Identify execution contexts with `id` for the `reentrancy-check` feature.
+///
+/// `id` must return a nonzero value that no other context running at the same time returns,
+/// e.g. the core number combined with the active interrupt, or 0 where the context is
+/// unknown, which skips the check. It replaces the per-thread identity of `std`, and without
+/// `std` it is what enables the check at all. Set it once at startup, before any write.
+///
+/// 为 `reentrancy-check` feature 使用 `id` 标识执行上下文。
+///
+/// `id` 必须返回一个非零值，且同时运行的其他上下文不会返回相同的值，例如组合核心编号和当前活动中断；
+/// 在上下文未知时返回 0，此时跳过检查。它会取代 `std` 下基于线程的标识，而没有 `std` 时，
+/// 只有设置它之后才会进行检查。应在启动时、任何写入之前设置一次。
+///
+/// # Example
+///
+/// ```
+/// fn context_id() -> usize {
+///     // Core number and active interrupt on a real target
+///     // 在实际目标上为核心编号和当前活动中断
+///     1
+/// }
+///
+/// lfrlock::set_context_id(context_id);
+/// ```
+pub fn set_context_id(id: fn() -> usize) {
+    CONTEXT_ID.store(id as *mut (), Ordering::Release);
+}
+
+/// Identity of the current context, 0 while unknown.
+///
+/// 当前上下文的标识，未知时为 0。
+#[inline]
+pub(crate) fn current() -> usize {
+    let id = CONTEXT_ID.load(Ordering::Acquire);
+    if !id.is_null() {
+        // SAFETY: only `set_context_id` stores into `CONTEXT_ID`, always a `fn() -> usize`
+        // SAFETY: 只有 `set_context_id` 会写入 `CONTEXT_ID`，写入的总是 `fn() -> usize`
+        let id: fn() -> usize = unsafe { core::mem::transmute(id) };
+        return id();
+    }
+    thread()
+}
+
+/// Address of a thread-local, unique among live threads.
+///
+/// 一个线程局部变量的地址，在存活的线程之间唯一。
+#[cfg(feature = "std")]
+#[inline]
+fn thread() -> usize {
+    std::thread_local! {
+        static IDENTITY: u8 = const { 0 };
+    }
+    IDENTITY.with(|identity| identity as *const u8 as usize)
+}
+
+#[cfg(not(feature = "std"))]
+#[inline]
+fn thread() -> usize {
+    0
+}
@@ -0,0 +1,171 @@
+//! Handle to a value that has been swapped out of a lock.
+//!
+//! The old value stays readable through the handle at once. Taking ownership has to wait
+//! until the garbage collector has dropped its own reference, which happens once every
+//! reader that could still see the value has moved on.
+//!
+//! 已从锁中换出的值的句柄。
+//!
+//! 旧值可以立即通过该句柄读取。取得所有权则必须等待垃圾回收器丢弃它自己的引用，
+//! 这在所有可能仍看到该值的读者都离开之后发生。
+
+use core::fmt;
+use core::ops::Deref;
+
+use lock_api::RawMutex;
+
+use crate::sync::Arc;
+use crate::{DefaultRawMutex, Shared};
+
+/// Retired - The old value returned by [`LfrLock::swap_retired`](crate::LfrLock::swap_retired)
+///
+/// Dereferences to the old value right away. Use [`try_into_inner`](Self::try_into_inner)
+/// or [`into_inner`](Self::into_inner) to take ownership once no reader can see it anymore,
+/// e.g. to shut down a connection pool cleanly.
+///
+/// Dropping the handle passes the value on like any other reclaimed value: once no reader
+/// can see it anymore, it goes to the reclaim hook or the drop queue if the lock has one, and
+/// is dropped otherwise. If the value is already unreachable, a hook runs on the dropping
+/// thread, so drop the handle outside the write guards of the same lock.
+///
+/// Retired - [`LfrLock::swap_retired`](crate::LfrLock::swap_retired) 返回的旧值
+///
+/// 可以立即解引用到旧值。当没有读者还能看到它时，使用 [`try_into_inner`](Self::try_into_inner)
+/// 或 [`into_inner`](Self::into_inner) 取得所有权，例如干净地关闭连接池。
+///
+/// drop 该句柄会像其他被回收的值一样传递该值：当没有读者还能看到它时，如果锁设置了回收钩子或
+/// drop 队列，则交给它们，否则直接 drop。如果该值已经不可达，钩子会在执行 drop 的线程上运行，
+/// 因此应在同一个锁的写入守卫之外 drop 该句柄。
+pub struct Retired<T: 'static, R: RawMutex = DefaultRawMutex> {
+    /// Only `None` once the value has been taken out.
+    ///
+    /// 仅在值被取出后为 `None`。
+    value: Option<Arc<T>>,
+    shared: Arc<Shared<T, R>>,
+    /// Version of the value, passed to the reclaim hook.
+    ///
+    /// 值的版本号，会传给回收钩子。
+    version: usize,
+    /// smr epoch at which the value was retired.
+    ///
+    /// 值退休时的 smr 纪元。
+    epoch: usize,
+}
+
+impl<T: 'static, R: RawMutex> Retired<T, R> {
+    #[inline]
+    pub(crate) fn new(
+        value: Arc<T>,
+        shared: Arc<Shared<T, R>>,
+        version: usize,
+        epoch: usize,
+    ) -> Self {
+        Retired {
+            value: Some(value),
+            shared,
+            version,
+            epoch,
+        }
+    }
+
+    #[inline]
+    fn value(&self) -> &Arc<T> {
+        self.value
+            .as_ref()
+            .expect("value taken out of a live handle")
+    }
+
+    /// Take ownership of the old value if no reader can see it anymore.
+    ///
+    /// Never blocks: if the write lock is busy or a reader still holds the value, the handle
+    /// is returned unchanged.
+    ///
+    /// 如果没有读者还能看到旧值，则取得其所有权。
+    ///
+    /// 永不阻塞：如果写入锁正忙或仍有读者持有该值，则原样返回句柄。
+    pub fn try_into_inner(mut self) -> Result<T, Self> {
+        if Arc::strong_count(self.value()) > 1 {
+            let Some(mut swap) = self.shared.try_lock() else {
+                return Err(self);
+            };
+            self.shared.reclaim_through(&mut swap, self.epoch);
+        }
+
+        match self.value.take().map(Arc::try_unwrap) {
+            Some(Ok(value)) => Ok(value),
+            Some(Err(value)) => {
+                self.value = Some(value);
+                Err(self)
+            }
+            None => unreachable!("value taken out of a live handle"),
+        }
+    }
+
+    /// Take ownership of the old value, waiting until no reader can see it anymore.
+    ///
+    /// Polls until every reader still holding the value has dropped its guard. Must not be
+    /// called while this thread holds a guard to the old value or the write lock.
+    ///
+    /// 取得旧值的所有权，等待直到没有读者还能看到它。
+    ///
+    /// 轮询直到所有仍持有该值的读者都 drop 了其守卫。不得在本线程持有旧值的守卫或写入锁时调用。
+    #[track_caller]
+    pub fn into_inner(self) -> T {
+        let mut retired = self;
+        loop {
+            if Arc::strong_count(retired.value()) > 1 {
+                let mut swap = retired.shared.lock();
+                retired.shared.reclaim_through(&mut swap, retired.epoch);
+            }
+
+            match retired.value.take().map(Arc::try_unwrap) {
+                Some(Ok(value)) => return value,
+                Some(Err(value)) => retired.value = Some(value),
+                None => unreachable!("value taken out of a live handle"),
+            }
+
+            #[cfg(feature = "std")]
+            std::thread::yield_now();
+            #[cfg(not(feature = "std"))]
+            core::hint::spin_loop();
+        }
+    }
+}
+
+impl<T: 'static, R: RawMutex> Drop for Retired<T, R> {
+    #[inline]
+    fn drop(&mut self) {
+        // The reclaimer only takes the value once nothing else shares it, so it does not
+        // matter whether the slot was reclaimed before or after
+        // 回收器只会在没有其他引用共享该值时接收它，因此槽在此之前还是之后被回收都无关紧要
+        if let Some(value) = self.value.take() {
+            self.shared.reclaimer.retire(self.version, value);
+            self.shared.reclaimer.drain();
+        }
+    }
+}
+
+impl<T: 'static, R: RawMutex> Deref for Retired<T, R> {
+    type Target = T;
+
+    #[inline]
+    fn deref(&self) -> &T {
+        self.value()
+    }
+}
+
+impl<T: 'static, R: RawMutex> AsRef<T> for Retired<T, R> {
+    #[inline]
+    fn as_ref(&self) -> &T {
+        self.value()
+    }
+}
+
+impl<T: fmt::Debug + 'static, R: RawMutex> fmt::Debug for Retired<T, R> {
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Retired")
+            .field("value", &**self.value())
+            .finish()
+    }
+}
@@ -0,0 +1,119 @@
+//! Consistent reads of several locks.
+//!
+//! Every lock is read once, then the version of every lock is checked again. A lock whose
+//! version did not change held the same value over the whole time, so all values were
+//! current together right after the last read. Otherwise the reads are retried, which only
+//! happens after another commit, so readers never wait for a writer.
+//!
+//! 对多个锁的一致读取。
+//!
+//! 每个锁读取一次，然后再次检查每个锁的版本号。版本号未变化的锁在整个期间都持有同一个值，
+//! 因此所有值在最后一次读取之后都同时是最新的。否则重新读取，而这只会在发生其他提交之后出现，
+//! 因此读者永远不会等待写者。
+
+use lock_api::RawMutex;
+
+use crate::sync::{Ordering, fence};
+use crate::{LfrLock, ReadGuard};
+
+mod sealed {
+    pub trait Sealed {}
+}
+
+/// Locks that [`snapshot`] can read together.
+///
+/// Implemented for tuples of up to 8 references to [`LfrLock`]s, which may hold different
+/// value types.
+///
+/// 可以由 [`snapshot`] 一起读取的锁。
+///
+/// 为最多 8 个 [`LfrLock`] 引用组成的元组实现，这些锁可以持有不同类型的值。
+pub trait Snapshot<'a>: sealed::Sealed {
+    /// Read guards in the same order as the locks.
+    ///
+    /// 与锁顺序相同的读取守卫。
+    type Guards;
+
+    #[doc(hidden)]
+    fn read(&self) -> Self::Guards;
+
+    #[doc(hidden)]
+    fn is_current(&self, guards: &Self::Guards) -> bool;
+}
+
+macro_rules! impl_snapshot {
+    ($($lock:ident $raw:ident $index:tt),+) => {
+        impl<'a, $($lock: 'static, $raw: RawMutex),+> sealed::Sealed
+            for ($(&'a LfrLock<$lock, $raw>,)+)
+        {
+        }
+
+        impl<'a, $($lock: 'static, $raw: RawMutex),+> Snapshot<'a>
+            for ($(&'a LfrLock<$lock, $raw>,)+)
+        {
+            type Guards = ($(ReadGuard<'a, $lock>,)+);
+
+            #[inline]
+            fn read(&self) -> Self::Guards {
+                ($(self.$index.read(),)+)
+            }
+
+            #[inline]
+            fn is_current(&self, guards: &Self::Guards) -> bool {
+                $(self.$index.version() == guards.$index.version())&&+
+            }
+        }
+    };
+}
+
+impl_snapshot!(A RA 0);
+impl_snapshot!(A RA 0, B RB 1);
+impl_snapshot!(A RA 0, B RB 1, C RC 2);
+impl_snapshot!(A RA 0, B RB 1, C RC 2, D RD 3);
+impl_snapshot!(A RA 0, B RB 1, C RC 2, D RD 3, E RE 4);
+impl_snapshot!(A RA 0, B RB 1, C RC 2, D RD 3, E RE 4, F RF 5);
+impl_snapshot!(A RA 0, B RB 1, C RC 2, D RD 3, E RE 4, F RF 5, G RG 6);
+impl_snapshot!(A RA 0, B RB 1, C RC 2, D RD 3, E RE 4, F RF 5, G RG 6, H RH 7);
+
+/// Read several locks at a single point of their commit history.
+///
+/// Takes a tuple of lock references and returns a tuple of read guards whose values were
+/// all current at the same instant, and never show part of a
+/// [`transaction`](crate::transaction). Lock-free: readers never wait, and only retry when
+/// another commit happened while they read.
+///
+/// 在提交历史中的同一时刻读取多个锁。
+///
+/// 接收锁引用的元组，返回读取守卫的元组，这些守卫的值在同一时刻都是最新的，并且永远不会只展示
+/// [`transaction`](crate::transaction) 的一部分。无锁：读者永远不会等待，只有在读取期间发生了
+/// 其他提交时才会重试。
+///
+/// # Example
+///
+/// ```
+/// use lfrlock::LfrLock;
+/// use std::collections::HashMap;
+///
+/// let routes = LfrLock::new(HashMap::from([("/api", "backend-1")]));
+/// let backends = LfrLock::new(vec!["backend-1"]);
+/// let weights = LfrLock::new(HashMap::from([("backend-1", 10)]));
+///
+/// let (routes, backends, weights) = lfrlock::snapshot((&routes, &backends, &weights));
+/// let backend = routes["/api"];
+/// assert!(backends.contains(&backend));
+/// assert_eq!(weights[backend], 10);
+/// ```
+#[inline]
+pub fn snapshot<'a, S: Snapshot<'a>>(locks: S) -> S::Guards {
+    loop {
+        let guards = locks.read();
+        // Orders the checks after the reads for every reader alike, so that two snapshots
+        // never disagree on which of two commits came first
+        // 对所有读者而言都将检查排在读取之后，使两个快照永远不会对两次提交的先后产生分歧
+        fence(Ordering::SeqCst);
+        if locks.is_current(&guards) {
+            return guards;
+        }
+        core::hint::spin_loop();
+    }
+}
@@ -0,0 +1,748 @@
+//! Read/write preference, chosen per lock.
+//!
+//! smr-swap picks the memory barrier strategy through a const generic. The enums here hold
+//! either flavour, so a single `LfrLock<T>` type covers both and the choice can be made at
+//! runtime. The variant never changes after construction, so the branch on every access is
+//! perfectly predicted.
+//!
+//! The adaptive strategy keeps one backend of each flavour. Only the active one is written;
+//! the dormant one holds placeholders, so it never keeps a value alive. Readers pick the
+//! backend from a flag and retry if they hit a placeholder, which only happens while the
+//! strategy is being switched.
+//!
+//! 按锁选择的读/写优先策略。
+//!
+//! smr-swap 通过 const 泛型选择内存屏障策略。这里的枚举可以持有任意一种，因此同一个 `LfrLock<T>`
+//! 类型可以覆盖两者，并在运行时做出选择。变体在构造后永不改变，因此每次访问时的分支都能被完美预测。
+//!
+//! 自适应策略为每种实现各保留一个后端。只有活跃的后端会被写入；休眠的后端持有占位符，
+//! 因此永远不会使某个值保持存活。读者根据标志选择后端，如果遇到占位符则重试，
+//! 这只会在切换策略期间发生。
+
+use core::cell::Cell;
+use core::ops::Deref;
+use core::time::Duration;
+
+#[cfg(feature = "std")]
+use std::{boxed::Box, collections::VecDeque};
+
+#[cfg(not(feature = "std"))]
+use alloc::{boxed::Box, collections::VecDeque};
+
+use smr_swap::{LocalReader, ReadGuard, ReaderFactory, SmrSwap};
+
+use crate::sync::{Arc, AtomicBool, AtomicUsize, Ordering};
+
+/// Strategy - Which side of the lock pays for the memory barriers
+///
+/// Set per lock with [`LfrLockBuilder::strategy`](crate::LfrLockBuilder::strategy). The
+/// default is [`WritePreferred`](Self::WritePreferred), or
+/// [`ReadPreferred`](Self::ReadPreferred) when the `read-preferred` feature is enabled.
+///
+/// Strategy - 由锁的哪一侧承担内存屏障的开销
+///
+/// 通过 [`LfrLockBuilder::strategy`](crate::LfrLockBuilder::strategy) 为每个锁单独设置。
+/// 默认为 [`WritePreferred`](Self::WritePreferred)，启用 `read-preferred` feature 时为
+/// [`ReadPreferred`](Self::ReadPreferred)。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Strategy {
+    /// Readers and writers both use regular fences. Suits locks that are written often.
+    ///
+    /// 读者和写者都使用普通的内存屏障。适用于频繁写入的锁。
+    WritePreferred,
+    /// Readers use compiler-only barriers and writers pay for a heavy, process-wide barrier.
+    /// Suits locks that are almost never written.
+    ///
+    /// 读者只使用编译器屏障，写者承担重量级的进程级屏障。适用于几乎从不写入的锁。
+    ReadPreferred,
+    /// Switch between the two at runtime, based on the observed reads per write and the time
+    /// writers wait for the write lock. Starts write-preferred.
+    ///
+    /// 根据观察到的每次写入对应的读取次数以及写者等待写入锁的时间，在运行时在两者之间切换。
+    /// 初始为写优先。
+    Adaptive(AdaptiveConfig),
+}
+
+impl Default for Strategy {
+    #[inline]
+    fn default() -> Self {
+        if cfg!(feature = "read-preferred") {
+            Strategy::ReadPreferred
+        } else {
+            Strategy::WritePreferred
+        }
+    }
+}
+
+/// AdaptiveConfig - When [`Strategy::Adaptive`] switches
+///
+/// The decision is taken once per window of commits. The lock turns read-preferred when
+/// readers do at least [`read_preferred_above`](Self::read_preferred_above) reads per commit,
+/// and back to write-preferred below [`write_preferred_below`](Self::write_preferred_below)
+/// reads per commit, or when writers waited longer than
+/// [`max_write_wait`](Self::max_write_wait) on average. Wait times are only measured with
+/// `std`.
+///
+/// AdaptiveConfig - [`Strategy::Adaptive`] 何时切换
+///
+/// 每个提交窗口做一次决定。当读者每次提交至少读取 [`read_preferred_above`](Self::read_preferred_above)
+/// 次时，锁切换为读优先；当每次提交的读取次数低于 [`write_preferred_below`](Self::write_preferred_below)，
+/// 或写者的平均等待时间超过 [`max_write_wait`](Self::max_write_wait) 时，切换回写优先。
+/// 只有在 `std` 环境下才会测量等待时间。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct AdaptiveConfig {
+    window: u32,
+    read_preferred_above: usize,
+    write_preferred_below: usize,
+    max_write_wait: Duration,
+}
+
+impl AdaptiveConfig {
+    /// Create a configuration with the default thresholds.
+    ///
+    /// 使用默认阈值创建配置。
+    #[inline]
+    pub const fn new() -> Self {
+        AdaptiveConfig {
+            window: 32,
+            read_preferred_above: 10_000,
+            write_preferred_below: 1_000,
+            max_write_wait: Duration::from_micros(50),
+        }
+    }
+
+    /// Number of commits between two decisions (default 32, at least 1).
+    ///
+    /// 两次决定之间的提交次数（默认 32，至少为 1）。
+    #[inline]
+    pub const fn window(mut self, commits: u32) -> Self {
+        self.window = if commits == 0 { 1 } else { commits };
+        self
+    }
+
+    /// Reads per commit from which to turn read-preferred (default 10 000).
+    ///
+    /// 切换为读优先所需的每次提交读取次数（默认 10 000）。
+    #[inline]
+    pub const fn read_preferred_above(mut self, reads: usize) -> Self {
+        self.read_preferred_above = reads;
+        self
+    }
+
+    /// Reads per commit below which to turn write-preferred again (default 1 000).
+    ///
+    /// 低于该每次提交读取次数时切换回写优先（默认 1 000）。
+    #[inline]
+    pub const fn write_preferred_below(mut self, reads: usize) -> Self {
+        self.write_preferred_below = reads;
+        self
+    }
+
+    /// Average writer wait from which to turn write-preferred again (default 50 µs).
+    ///
+    /// 切换回写优先所需的写者平均等待时间（默认 50 µs）。
+    #[inline]
+    pub const fn max_write_wait(mut self, wait: Duration) -> Self {
+        self.max_write_wait = wait;
+        self
+    }
+}
+
+impl Default for AdaptiveConfig {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Reads are added to the shared counter in batches, to keep the read path free of
+/// contended atomics.
+///
+/// 读取以批次累加到共享计数器，使读取路径不涉及竞争的原子操作。
+const READ_BATCH: u32 = 64;
+
+/// Forward a call to whichever flavour an enum holds.
+///
+/// 将调用转发给枚举所持有的那一种实现。
+macro_rules! dispatch {
+    ($value:expr, $inner:ident => $body:expr) => {
+        match $value {
+            Self::Write($inner) => $body,
+            Self::Read($inner) => $body,
+            Self::Adaptive($inner) => $body,
+        }
+    };
+}
+
+/// `SmrSwap` of either strategy.
+///
+/// 任一策略的 `SmrSwap`。
+pub(crate) enum Swap<T: 'static> {
+    Write(SmrSwap<T, false>),
+    Read(SmrSwap<T, true>),
+    Adaptive(Box<AdaptiveSwap<T>>),
+}
+
+impl<T: 'static> Swap<T> {
+    #[inline]
+    pub(crate) fn new(initial: T, strategy: Strategy) -> Self {
+        match strategy {
+            Strategy::WritePreferred => Swap::Write(SmrSwap::new(initial)),
+            Strategy::ReadPreferred => Swap::Read(SmrSwap::new_read_preferred(initial)),
+            Strategy::Adaptive(config) => {
+                Swap::Adaptive(Box::new(AdaptiveSwap::new(initial, config)))
+            }
+        }
+    }
+
+    #[inline]
+    pub(crate) fn local_reader(&self) -> Local<T> {
+        match self {
+            Swap::Write(swap) => Local::Write(swap.local_reader()),
+            Swap::Read(swap) => Local::Read(swap.local_reader()),
+            Swap::Adaptive(swap) => Local::Adaptive(swap.local_reader()),
+        }
+    }
+
+    #[inline]
+    pub(crate) fn get(&self) -> &T {
+        dispatch!(self, swap => swap.get())
+    }
+
+    #[inline]
+    pub(crate) fn store(&mut self, value: T) {
+        dispatch!(self, swap => swap.store(value))
+    }
+
+    #[inline]
+    pub(crate) fn version(&self) -> usize {
+        dispatch!(self, swap => swap.version())
+    }
+
+    #[inline]
+    pub(crate) fn garbage_count(&self) -> usize {
+        dispatch!(self, swap => swap.garbage_count())
+    }
+
+    #[inline]
+    pub(crate) fn collect(&mut self) {
+        dispatch!(self, swap => swap.collect())
+    }
+
+    /// Whether a value retired before epoch `since` may still be waiting for reclamation.
+    ///
+    /// 在纪元 `since` 之前退休的值是否可能仍在等待回收。
+    #[inline]
+    pub(crate) fn holds_retired_before(&self, since: usize) -> bool {
+        match self {
+            // Every store adds one retired value, reclaimed oldest first, so at most the
+            // values retired from `since` on may be left
+            // 每次存储都会添加一个退休值，并按从旧到新的顺序回收，
+            // 因此最多只剩下从 `since` 开始退休的值
+            Swap::Write(swap) => swap.garbage_count() > swap.version().wrapping_sub(since),
+            Swap::Read(swap) => swap.garbage_count() > swap.version().wrapping_sub(since),
+            Swap::Adaptive(swap) => swap.holds_retired_before(since),
+        }
+    }
+
+    /// Add the time a writer waited for the write lock to the current window.
+    ///
+    /// 将写者等待写入锁的时间计入当前窗口。
+    #[cfg(feature = "std")]
+    #[inline]
+    pub(crate) fn record_wait(&mut self, wait: Duration) {
+        if let Swap::Adaptive(swap) = self {
+            swap.wait += wait;
+        }
+    }
+
+    /// Count a commit and switch strategy at the end of a window if needed.
+    ///
+    /// `republish` copies the current value into the backend taking over.
+    ///
+    /// 计入一次提交，并在窗口结束时按需切换策略。
+    ///
+    /// `republish` 将当前值复制到接管的后端。
+    #[inline]
+    pub(crate) fn record_commit(&mut self, republish: impl FnOnce(&T) -> T) {
+        if let Swap::Adaptive(swap) = self {
+            swap.record_commit(republish);
+        }
+    }
+}
+
+/// Retirement epochs of one adaptive backend, mirroring its garbage queue.
+///
+/// smr reclaims the oldest entries first, so the mirror is trimmed from the front to the
+/// length of the queue. `None` marks a retired placeholder.
+///
+/// 自适应后端之一的退休纪元，与其垃圾队列一一对应。
+///
+/// smr 先回收最旧的条目，因此镜像从前端裁剪到与队列相同的长度。`None` 表示退休的占位符。
+struct Retirements {
+    epochs: VecDeque<Option<usize>>,
+    values: usize,
+}
+
+impl Retirements {
+    #[inline]
+    fn new() -> Self {
+        Retirements {
+            epochs: VecDeque::new(),
+            values: 0,
+        }
+    }
+
+    /// Store `value` into `swap`, recording the retirement of its current value.
+    ///
+    /// 将 `value` 存入 `swap`，并记录其当前值的退休。
+    fn store<T, const RP: bool>(
+        &mut self,
+        swap: &mut SmrSwap<Option<T>, RP>,
+        epoch: usize,
+        value: Option<T>,
+    ) {
+        let retired = swap.get().is_some().then_some(epoch);
+        self.values += usize::from(retired.is_some());
+        self.epochs.push_back(retired);
+        swap.store(value);
+        self.trim(swap.garbage_count());
+    }
+
+    /// Collect `swap` if it holds retired values.
+    ///
+    /// Skipped otherwise, as collecting a read-preferred backend costs a heavy barrier.
+    ///
+    /// 如果 `swap` 持有退休的值则进行回收。
+    ///
+    /// 否则跳过，因为回收读优先后端需要一次重量级屏障。
+    fn collect<T, const RP: bool>(&mut self, swap: &mut SmrSwap<Option<T>, RP>) {
+        if self.values > 0 {
+            swap.collect();
+            self.trim(swap.garbage_count());
+        }
+    }
+
+    fn trim(&mut self, len: usize) {
+        while self.epochs.len() > len {
+            if let Some(Some(_)) = self.epochs.pop_front() {
+                self.values -= 1;
+            }
+        }
+    }
+
+    /// Epoch of the oldest retired value still waiting.
+    ///
+    /// 仍在等待的最旧退休值的纪元。
+    fn oldest(&self) -> Option<usize> {
+        self.epochs.iter().flatten().next().copied()
+    }
+}
+
+/// State shared by an adaptive lock's writer and readers.
+///
+/// 自适应锁的写者和读者共享的状态。
+struct AdaptiveState {
+    config: AdaptiveConfig,
+    /// Which backend readers should use.
+    ///
+    /// 读者应使用的后端。
+    read_preferred: AtomicBool,
+    /// Reads since the last decision.
+    ///
+    /// 自上次决定以来的读取次数。
+    reads: AtomicUsize,
+}
+
+/// Writer side of [`Strategy::Adaptive`].
+///
+/// The epoch exposed through [`Swap::version`] is the sum of both backends' versions, which
+/// grows by one with every store into either backend.
+///
+/// [`Strategy::Adaptive`] 的写者侧。
+///
+/// 通过 [`Swap::version`] 暴露的纪元是两个后端版本号之和，每次向任一后端存储时增加 1。
+pub(crate) struct AdaptiveSwap<T: 'static> {
+    write: SmrSwap<Option<T>, false>,
+    read: SmrSwap<Option<T>, true>,
+    write_retired: Retirements,
+    read_retired: Retirements,
+    state: Arc<AdaptiveState>,
+    commits: u32,
+    wait: Duration,
+}
+
+impl<T: 'static> AdaptiveSwap<T> {
+    fn new(initial: T, config: AdaptiveConfig) -> Self {
+        AdaptiveSwap {
+            write: SmrSwap::new(Some(initial)),
+            read: SmrSwap::new_read_preferred(None),
+            write_retired: Retirements::new(),
+            read_retired: Retirements::new(),
+            state: Arc::new(AdaptiveState {
+                config,
+                read_preferred: AtomicBool::new(false),
+                reads: AtomicUsize::new(0),
+            }),
+            commits: 0,
+            wait: Duration::ZERO,
+        }
+    }
+
+    #[inline]
+    fn is_read_preferred(&self) -> bool {
+        // Only the writer changes the flag
+        // 只有写者会修改该标志
+        self.state.read_preferred.load(Ordering::Relaxed)
+    }
+
+    fn local_reader(&self) -> AdaptiveLocal<T> {
+        AdaptiveLocal {
+            write: self.write.local_reader(),
+            read: self.read.local_reader(),
+            state: self.state.clone(),
+            reads: Cell::new(0),
+        }
+    }
+
+    #[inline]
+    fn get(&self) -> &T {
+        let current = if self.is_read_preferred() {
+            self.read.get()
+        } else {
+            self.write.get()
+        };
+        match current {
+            Some(value) => value,
+            None => unreachable!("the active backend never holds a placeholder"),
+        }
+    }
+
+    #[inline]
+    fn store(&mut self, value: T) {
+        let epoch = self.version();
+        if self.is_read_preferred() {
+            self.read_retired.store(&mut self.read, epoch, Some(value));
+        } else {
+            self.write_retired
+                .store(&mut self.write, epoch, Some(value));
+        }
+    }
+
+    #[inline]
+    fn version(&self) -> usize {
+        self.write.version().wrapping_add(self.read.version())
+    }
+
+    #[inline]
+    fn garbage_count(&self) -> usize {
+        self.write_retired.values + self.read_retired.values
+    }
+
+    #[inline]
+    fn collect(&mut self) {
+        self.write_retired.collect(&mut self.write);
+        self.read_retired.collect(&mut self.read);
+    }
+
+    fn holds_retired_before(&self, since: usize) -> bool {
+        [self.write_retired.oldest(), self.read_retired.oldest()]
+            .into_iter()
+            .flatten()
+            .any(|epoch| since.wrapping_sub(epoch).wrapping_sub(1) < usize::MAX / 2)
+    }
+
+    fn record_commit(&mut self, republish: impl FnOnce(&T) -> T) {
+        self.commits += 1;
+        let config = self.state.config;
+        if self.commits < config.window {
+            return;
+        }
+
+        let reads = self.state.reads.swap(0, Ordering::Relaxed) / self.commits as usize;
+        let wait = self.wait / self.commits;
+        self.commits = 0;
+        self.wait = Duration::ZERO;
+
+        let read_preferred = if self.is_read_preferred() {
+            reads >= config.write_preferred_below && wait < config.max_write_wait
+        } else {
+            reads >= config.read_preferred_above && wait < config.max_write_wait
+        };
+        if read_preferred != self.is_read_preferred() {
+            self.switch(read_preferred, republish);
+        }
+    }
+
+    /// Make the other backend the active one.
+    ///
+    /// The current value is copied over before readers are redirected. The old backend then
+    /// gets enough placeholders that every value it retired can be reclaimed, and readers
+    /// that still find it through a stale flag see a placeholder and retry.
+    ///
+    /// 使另一个后端成为活跃后端。
+    ///
+    /// 在重定向读者之前先复制当前值。随后旧后端会被存入足够多的占位符，使其退休的所有值都可以被回收，
+    /// 而通过过时标志仍然找到它的读者会看到占位符并重试。
+    fn switch(&mut self, read_preferred: bool, republish: impl FnOnce(&T) -> T) {
+        let current = republish(self.get());
+        let epoch = self.version();
+        if read_preferred {
+            self.read_retired
+                .store(&mut self.read, epoch, Some(current));
+        } else {
+            self.write_retired
+                .store(&mut self.write, epoch, Some(current));
+        }
+        self.state
+            .read_preferred
+            .store(read_preferred, Ordering::Release);
+
+        for _ in 0..3 {
+            let epoch = self.version();
+            if read_preferred {
+                self.write_retired.store(&mut self.write, epoch, None);
+            } else {
+                self.read_retired.store(&mut self.read, epoch, None);
+            }
+        }
+        self.collect();
+    }
+}
+
+/// `LocalReader` of either strategy.
+///
+/// 任一策略的 `LocalReader`。
+pub(crate) enum Local<T: 'static> {
+    Write(LocalReader<T, false>),
+    Read(LocalReader<T, true>),
+    Adaptive(AdaptiveLocal<T>),
+}
+
+impl<T: 'static> Local<T> {
+    #[inline]
+    pub(crate) fn strategy(&self) -> Strategy {
+        match self {
+            Local::Write(_) => Strategy::WritePreferred,
+            Local::Read(_) => Strategy::ReadPreferred,
+            Local::Adaptive(local) => Strategy::Adaptive(local.state.config),
+        }
+    }
+
+    /// The strategy readers currently use, never [`Strategy::Adaptive`].
+    ///
+    /// 读者当前使用的策略，永远不会是 [`Strategy::Adaptive`]。
+    #[inline]
+    pub(crate) fn active_strategy(&self) -> Strategy {
+        match self {
+            Local::Adaptive(local) if local.state.read_preferred.load(Ordering::Relaxed) => {
+                Strategy::ReadPreferred
+            }
+            Local::Adaptive(_) => Strategy::WritePreferred,
+            _ => self.strategy(),
+        }
+    }
+
+    #[inline]
+    pub(crate) fn load(&self) -> Pinned<'_, T> {
+        match self {
+            Local::Write(local) => Pinned::Write(local.load()),
+            Local::Read(local) => Pinned::Read(local.load()),
+            Local::Adaptive(local) => local.load(),
+        }
+    }
+
+    #[inline]
+    pub(crate) fn map<F, U>(&self, f: F) -> U
+    where
+        F: FnOnce(&T) -> U,
+    {
+        dispatch!(self, local => local.map(f))
+    }
+
+    #[inline]
+    pub(crate) fn factory(&self) -> Factory<T> {
+        match self {
+            Local::Write(local) => Factory::Write(local.reader_factory()),
+            Local::Read(local) => Factory::Read(local.reader_factory()),
+            Local::Adaptive(local) => Factory::Adaptive(AdaptiveFactory {
+                write: local.write.reader_factory(),
+                read: local.read.reader_factory(),
+                state: local.state.clone(),
+            }),
+        }
+    }
+
+    #[inline]
+    pub(crate) fn into_factory(self) -> Factory<T> {
+        match self {
+            Local::Write(local) => Factory::Write(local.into_swmr()),
+            Local::Read(local) => Factory::Read(local.into_swmr()),
+            Local::Adaptive(local) => Factory::Adaptive(AdaptiveFactory {
+                write: local.write.into_swmr(),
+                read: local.read.into_swmr(),
+                state: local.state,
+            }),
+        }
+    }
+}
+
+impl<T: 'static> Clone for Local<T> {
+    #[inline]
+    fn clone(&self) -> Self {
+        match self {
+            Local::Write(local) => Local::Write(local.clone()),
+            Local::Read(local) => Local::Read(local.clone()),
+            Local::Adaptive(local) => Local::Adaptive(AdaptiveLocal {
+                write: local.write.clone(),
+                read: local.read.clone(),
+                state: local.state.clone(),
+                reads: Cell::new(0),
+            }),
+        }
+    }
+}
+
+/// Reader side of [`Strategy::Adaptive`].
+///
+/// [`Strategy::Adaptive`] 的读者侧。
+pub(crate) struct AdaptiveLocal<T: 'static> {
+    write: LocalReader<Option<T>, false>,
+    read: LocalReader<Option<T>, true>,
+    state: Arc<AdaptiveState>,
+    /// Reads not yet added to the shared counter.
+    ///
+    /// 尚未累加到共享计数器的读取次数。
+    reads: Cell<u32>,
+}
+
+impl<T: 'static> AdaptiveLocal<T> {
+    #[inline]
+    fn load(&self) -> Pinned<'_, T> {
+        let reads = self.reads.get() + 1;
+        if reads == READ_BATCH {
+            self.state
+                .reads
+                .fetch_add(READ_BATCH as usize, Ordering::Relaxed);
+            self.reads.set(0);
+        } else {
+            self.reads.set(reads);
+        }
+
+        // A placeholder means the strategy was switched after the flag was read
+        // 占位符意味着在读取标志之后策略已被切换
+        loop {
+            if self.state.read_preferred.load(Ordering::Acquire) {
+                let guard = self.read.load();
+                if guard.is_some() {
+                    return Pinned::AdaptiveRead(guard);
+                }
+            } else {
+                let guard = self.write.load();
+                if guard.is_some() {
+                    return Pinned::AdaptiveWrite(guard);
+                }
+            }
+        }
+    }
+
+    #[inline]
+    fn map<F, U>(&self, f: F) -> U
+    where
+        F: FnOnce(&T) -> U,
+    {
+        f(&self.load())
+    }
+}
+
+/// `ReaderFactory` of either strategy.
+///
+/// 任一策略的 `ReaderFactory`。
+pub(crate) enum Factory<T: 'static> {
+    Write(ReaderFactory<T, false>),
+    Read(ReaderFactory<T, true>),
+    Adaptive(AdaptiveFactory<T>),
+}
+
+impl<T: 'static> Factory<T> {
+    #[inline]
+    pub(crate) fn local_reader(&self) -> Local<T> {
+        match self {
+            Factory::Write(factory) => Local::Write(factory.local_reader()),
+            Factory::Read(factory) => Local::Read(factory.local_reader()),
+            Factory::Adaptive(factory) => Local::Adaptive(AdaptiveLocal {
+                write: factory.write.local_reader(),
+                read: factory.read.local_reader(),
+                state: factory.state.clone(),
+                reads: Cell::new(0),
+            }),
+        }
+    }
+}
+
+impl<T: 'static> Clone for Factory<T> {
+    #[inline]
+    fn clone(&self) -> Self {
+        match self {
+            Factory::Write(factory) => Factory::Write(factory.clone()),
+            Factory::Read(factory) => Factory::Read(factory.clone()),
+            Factory::Adaptive(factory) => Factory::Adaptive(AdaptiveFactory {
+                write: factory.write.clone(),
+                read: factory.read.clone(),
+                state: factory.state.clone(),
+            }),
+        }
+    }
+}
+
+/// Factory side of [`Strategy::Adaptive`].
+///
+/// [`Strategy::Adaptive`] 的工厂侧。
+pub(crate) struct AdaptiveFactory<T: 'static> {
+    write: ReaderFactory<Option<T>, false>,
+    read: ReaderFactory<Option<T>, true>,
+    state: Arc<AdaptiveState>,
+}
+
+/// smr `ReadGuard` of either strategy.
+///
+/// 任一策略的 smr `ReadGuard`。
+pub(crate) enum Pinned<'a, T: 'static> {
+    Write(ReadGuard<'a, T, false>),
+    Read(ReadGuard<'a, T, true>),
+    /// Never holds a placeholder, see [`AdaptiveLocal::load`].
+    ///
+    /// 永远不会持有占位符，参见 [`AdaptiveLocal::load`]。
+    AdaptiveWrite(ReadGuard<'a, Option<T>, false>),
+    AdaptiveRead(ReadGuard<'a, Option<T>, true>),
+}
+
+impl<'a, T: 'static> Deref for Pinned<'a, T> {
+    type Target = T;
+
+    #[inline]
+    fn deref(&self) -> &T {
+        let value = match self {
+            Pinned::Write(guard) => return guard,
+            Pinned::Read(guard) => return guard,
+            Pinned::AdaptiveWrite(guard) => &**guard,
+            Pinned::AdaptiveRead(guard) => &**guard,
+        };
+        match value {
+            Some(value) => value,
+            None => unreachable!("readers never keep a placeholder"),
+        }
+    }
+}
+
+impl<'a, T: 'static> Clone for Pinned<'a, T> {
+    #[inline]
+    fn clone(&self) -> Self {
+        match self {
+            Pinned::Write(guard) => Pinned::Write(guard.clone()),
+            Pinned::Read(guard) => Pinned::Read(guard.clone()),
+            Pinned::AdaptiveWrite(guard) => Pinned::AdaptiveWrite(guard.clone()),
+            Pinned::AdaptiveRead(guard) => Pinned::AdaptiveRead(guard.clone()),
+        }
+    }
+}
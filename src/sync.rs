@@ -0,0 +1,33 @@
+//! Atomics and `Arc` used throughout the crate.
+//!
+//! With the `portable-atomic` feature, they come from `portable-atomic` and
+//! `portable-atomic-util`, so that targets without native compare-and-swap, such as
+//! `thumbv6m-none-eabi`, can provide them, e.g. through a critical section. That `Arc`
+//! cannot coerce to unsized types on stable Rust, so `Arc<dyn Fn>` is built from a `Box`.
+//!
+//! 整个 crate 使用的原子类型和 `Arc`。
+//!
+//! 启用 `portable-atomic` feature 时，它们来自 `portable-atomic` 和 `portable-atomic-util`，
+//! 使没有原生比较并交换的目标（例如 `thumbv6m-none-eabi`）也能提供它们，例如通过临界区。
+//! 该 `Arc` 在稳定版 Rust 上无法强制转换为非固定大小类型，因此 `Arc<dyn Fn>` 由 `Box` 构建。
+
+#[cfg(not(feature = "portable-atomic"))]
+pub(crate) use core::sync::atomic::{AtomicBool, AtomicUsize, Ordering, fence};
+
+#[cfg(feature = "portable-atomic")]
+pub(crate) use portable_atomic::{AtomicBool, AtomicUsize, Ordering, fence};
+
+#[cfg(all(feature = "reentrancy-check", not(feature = "portable-atomic")))]
+pub(crate) use core::sync::atomic::AtomicPtr;
+
+#[cfg(all(feature = "reentrancy-check", feature = "portable-atomic"))]
+pub(crate) use portable_atomic::AtomicPtr;
+
+#[cfg(all(feature = "std", not(feature = "portable-atomic")))]
+pub(crate) use std::sync::Arc;
+
+#[cfg(all(not(feature = "std"), not(feature = "portable-atomic")))]
+pub(crate) use alloc::sync::Arc;
+
+#[cfg(feature = "portable-atomic")]
+pub(crate) use portable_atomic_util::Arc;
@@ -0,0 +1,399 @@
+//! Atomic updates across several locks, and reads that see them as one unit.
+//!
+//! A transaction runs its closure without holding any lock, recording the version of every
+//! lock it reads and keeping its writes as drafts. To commit, it takes the writer Mutex of
+//! every lock it touched in address order, so two transactions never wait on each other in
+//! a cycle, and checks that none of them changed meanwhile. Otherwise it releases them and
+//! runs the closure again.
+//!
+//! The drafts are then stored in slots that still show the prior value to readers, until a
+//! flag shared by all of them is set. Setting that flag is the single instant at which every
+//! write of the transaction becomes visible. Each slot is then replaced by a plain copy, so
+//! the prior value is reclaimed like any other.
+//!
+//! 跨多个锁的原子更新，以及将其视为一个整体的读取。
+//!
+//! 事务在不持有任何锁的情况下运行其闭包，记录其读取的每个锁的版本号，并将其写入保存为草稿。
+//! 提交时，它按地址顺序获取所涉及的每个锁的写入 Mutex，因此两个事务永远不会循环等待对方，
+//! 并检查这些锁在此期间都没有变化。否则它会释放这些锁并重新运行闭包。
+//!
+//! 之后草稿被存储到仍向读者展示之前的值的槽中，直到它们共享的标志被设置。设置该标志是事务的
+//! 所有写入同时变为可见的唯一时刻。随后每个槽都被替换为普通的副本，因此之前的值会像其他值一样被回收。
+
+use core::any::Any;
+use core::mem::ManuallyDrop;
+
+#[cfg(feature = "std")]
+use std::{boxed::Box, vec::Vec};
+
+#[cfg(not(feature = "std"))]
+use alloc::{boxed::Box, vec::Vec};
+
+use lock_api::RawMutex;
+
+use crate::reclaim::SwapGuard;
+use crate::sync::{Arc, AtomicBool, Ordering, fence};
+use crate::{LfrLock, ReadGuard, Slot};
+
+/// The value a slot stored by a transaction replaced.
+///
+/// 由事务存储的槽所替换的值。
+pub(crate) struct Prior<T> {
+    /// Set once every write of the transaction is stored.
+    ///
+    /// 在事务的所有写入都存储完毕后设置。
+    committed: Arc<AtomicBool>,
+    pub(crate) value: Arc<T>,
+    pub(crate) version: usize,
+}
+
+impl<T> Prior<T> {
+    /// Whether readers should still see this value.
+    ///
+    /// 读者是否仍应看到此值。
+    #[inline]
+    pub(crate) fn is_pending(&self) -> bool {
+        !self.committed.load(Ordering::Acquire)
+    }
+}
+
+/// Transaction - Reads and writes of several locks that commit as one unit
+///
+/// Passed to the closure of [`transaction`]. Every lock is read at most once per attempt:
+/// later reads of the same lock return the same value, or the draft once it was written.
+///
+/// Transaction - 作为一个整体提交的对多个锁的读写
+///
+/// 传递给 [`transaction`] 的闭包。每次尝试中每个锁最多读取一次：之后对同一个锁的读取返回相同的值，
+/// 或在写入之后返回草稿。
+pub struct Transaction<'a> {
+    entries: Vec<Box<dyn Entry<'a> + 'a>>,
+}
+
+impl<'a> Transaction<'a> {
+    /// Read the value of `lock` as seen by this transaction.
+    ///
+    /// 读取此事务所看到的 `lock` 的值。
+    #[inline]
+    pub fn read<T: 'static, R: RawMutex>(&mut self, lock: &'a LfrLock<T, R>) -> &T {
+        let (current, draft) = self.entry(lock).parts();
+        match draft.downcast_mut::<Option<T>>().and_then(Option::as_mut) {
+            Some(draft) => draft,
+            None => current.downcast_ref().expect("entry matches its lock"),
+        }
+    }
+
+    /// Get mutable access to the draft of `lock`, copied from its value on first access -
+    /// Requires T to implement Clone
+    ///
+    /// 获取 `lock` 草稿的可变访问，首次访问时从其值复制 - 需要 T 实现 Clone
+    #[inline]
+    pub fn write<T: Clone + 'static, R: RawMutex>(&mut self, lock: &'a LfrLock<T, R>) -> &mut T {
+        let (current, draft) = self.entry(lock).parts();
+        let draft = draft
+            .downcast_mut::<Option<T>>()
+            .expect("entry matches its lock");
+        draft.get_or_insert_with(|| {
+            T::clone(current.downcast_ref().expect("entry matches its lock"))
+        })
+    }
+
+    /// Replace the draft of `lock` with `value`.
+    ///
+    /// 用 `value` 替换 `lock` 的草稿。
+    #[inline]
+    pub fn store<T: 'static, R: RawMutex>(&mut self, lock: &'a LfrLock<T, R>, value: T) {
+        let (_, draft) = self.entry(lock).parts();
+        *draft
+            .downcast_mut::<Option<T>>()
+            .expect("entry matches its lock") = Some(value);
+    }
+
+    /// Find the entry of `lock`, reading it on first access.
+    ///
+    /// 查找 `lock` 的条目，首次访问时读取它。
+    fn entry<T: 'static, R: RawMutex>(&mut self, lock: &'a LfrLock<T, R>) -> &mut dyn Entry<'a> {
+        let key = key(lock);
+        let index = match self.entries.iter().position(|entry| entry.key() == key) {
+            Some(index) => index,
+            None => {
+                let read = lock.read();
+                self.entries.push(Box::new(Access {
+                    lock,
+                    version: read.version(),
+                    read: Some(read),
+                    draft: None,
+                    swap: None,
+                    staged: false,
+                }));
+                self.entries.len() - 1
+            }
+        };
+        &mut *self.entries[index]
+    }
+
+    /// Publish the drafts if nothing read has changed, returning whether it did.
+    ///
+    /// 如果读取的内容都没有变化则发布草稿，返回是否已发布。
+    #[track_caller]
+    fn commit(&mut self) -> bool {
+        // Without writes, checking that the values read are still current is enough
+        // 没有写入时，检查读取的值仍是最新的就足够了
+        if !self.entries.iter().any(|entry| entry.is_written()) {
+            // Same check as `snapshot`
+            // 与 `snapshot` 相同的检查
+            fence(Ordering::SeqCst);
+            return self.entries.iter().all(|entry| entry.is_current());
+        }
+
+        self.entries.sort_unstable_by_key(|entry| entry.key());
+        for entry in &mut self.entries {
+            entry.lock();
+        }
+        if !self.entries.iter().all(|entry| entry.is_current()) {
+            return false;
+        }
+
+        // Anything that may wait or call back runs before the first write is stored
+        // 任何可能等待或回调的操作都在存储第一个写入之前运行
+        for entry in &mut self.entries {
+            entry.prepare();
+        }
+        let committed = Arc::new(AtomicBool::new(false));
+        for entry in &mut self.entries {
+            entry.stage(&committed);
+        }
+        committed.store(true, Ordering::Release);
+        for entry in &mut self.entries {
+            entry.finish();
+        }
+        true
+    }
+}
+
+impl Drop for Transaction<'_> {
+    #[inline]
+    fn drop(&mut self) {
+        // Release in reverse order of acquiring, as critical sections require
+        // 按获取的相反顺序释放，这是临界区所要求的
+        while self.entries.pop().is_some() {}
+    }
+}
+
+/// Identity of the state shared by every handle of `lock`.
+///
+/// `lock` 的所有句柄共享的状态的标识。
+#[inline]
+fn key<T: 'static, R: RawMutex>(lock: &LfrLock<T, R>) -> usize {
+    Arc::as_ptr(&lock.shared).cast::<()>() as usize
+}
+
+/// A lock touched by a transaction, with its value type erased.
+///
+/// 被事务涉及的锁，其值类型已被擦除。
+trait Entry<'a> {
+    fn key(&self) -> usize;
+
+    /// The value read, as `&T`, and the draft, as `&mut Option<T>`.
+    ///
+    /// 读取的值（`&T`）和草稿（`&mut Option<T>`）。
+    fn parts(&mut self) -> (&dyn Any, &mut dyn Any);
+
+    fn is_written(&self) -> bool;
+
+    /// Whether the version read is still the current one.
+    ///
+    /// 读取的版本号是否仍是当前版本号。
+    fn is_current(&self) -> bool;
+
+    /// Drop the value read and acquire the writer Mutex.
+    ///
+    /// drop 读取的值并获取写入 Mutex。
+    #[track_caller]
+    fn lock(&mut self);
+
+    /// Drop a draft that `skip_equal` finds unchanged and apply the backlog policy.
+    ///
+    /// drop 被 `skip_equal` 判定为未改变的草稿，并应用积压策略。
+    fn prepare(&mut self);
+
+    /// Store the draft, still showing the current value to readers until `committed`.
+    ///
+    /// 存储草稿，在 `committed` 之前仍向读者展示当前值。
+    fn stage(&mut self, committed: &Arc<AtomicBool>);
+
+    /// Replace the staged slot by a plain one and wake subscribers.
+    ///
+    /// 将暂存的槽替换为普通的槽并唤醒订阅者。
+    fn finish(&mut self);
+}
+
+struct Access<'a, T: 'static, R: RawMutex> {
+    lock: &'a LfrLock<T, R>,
+    /// Visible version of the value read.
+    ///
+    /// 读取的值的可见版本号。
+    version: usize,
+    /// Dropped before locking, so that a backlog that waits for readers never waits for
+    /// this one.
+    ///
+    /// 在加锁之前 drop，使等待读者的积压策略永远不会等待这个读者。
+    read: Option<ReadGuard<'a, T>>,
+    draft: Option<T>,
+    swap: Option<SwapGuard<'a, T, R>>,
+    staged: bool,
+}
+
+impl<'a, T: 'static, R: RawMutex> Entry<'a> for Access<'a, T, R> {
+    #[inline]
+    fn key(&self) -> usize {
+        key(self.lock)
+    }
+
+    #[inline]
+    fn parts(&mut self) -> (&dyn Any, &mut dyn Any) {
+        let read: &T = self.read.as_ref().expect("read before committing");
+        (read, &mut self.draft)
+    }
+
+    #[inline]
+    fn is_written(&self) -> bool {
+        self.draft.is_some()
+    }
+
+    #[inline]
+    fn is_current(&self) -> bool {
+        match &self.swap {
+            Some(swap) => swap.get().version == self.version,
+            None => self.lock.version() == self.version,
+        }
+    }
+
+    #[inline]
+    #[track_caller]
+    fn lock(&mut self) {
+        self.read = None;
+        let shared = &*self.lock.shared;
+        self.swap = Some(if self.is_written() {
+            shared.write_lock()
+        } else {
+            shared.lock()
+        });
+    }
+
+    fn prepare(&mut self) {
+        let (Some(swap), Some(draft)) = (&mut self.swap, &self.draft) else {
+            return;
+        };
+        let shared = &*self.lock.shared;
+        if let Some(eq) = shared.skip_equal
+            && eq(&swap.get().value, draft)
+        {
+            self.draft = None;
+            return;
+        }
+        shared.make_room(swap);
+    }
+
+    fn stage(&mut self, committed: &Arc<AtomicBool>) {
+        let (Some(swap), Some(value)) = (&mut self.swap, self.draft.take()) else {
+            return;
+        };
+        let current = swap.get();
+        let slot = Slot {
+            value: ManuallyDrop::new(Arc::new(value)),
+            version: current.version.wrapping_add(1),
+            reclaimer: self.lock.shared.reclaimer.clone(),
+            prior: Some(Prior {
+                committed: committed.clone(),
+                value: Arc::clone(&current.value),
+                version: current.version,
+            }),
+        };
+        swap.store(slot);
+        self.staged = true;
+    }
+
+    fn finish(&mut self) {
+        let Some(swap) = &mut self.swap else {
+            return;
+        };
+        if !self.staged {
+            return;
+        }
+        let plain = swap.get().republish();
+        swap.store(plain);
+        swap.record_commit(Slot::republish);
+        self.lock.shared.watchers.notify_all();
+    }
+}
+
+/// Run `f` as a transaction over the locks it reads and writes, and commit its writes as
+/// one unit.
+///
+/// `f` runs without holding any lock, with reads through [`Transaction::read`] and writes
+/// through [`Transaction::write`] or [`Transaction::store`]. Once it returns, the writer
+/// locks of every lock it touched are taken in a fixed global order, so transactions never
+/// deadlock among themselves. If any of those locks changed since `f` read it, `f` runs
+/// again, so it may be called several times and should be free of side effects.
+///
+/// Every write becomes visible to readers at the same instant. [`snapshot`](crate::snapshot)
+/// reads several locks consistently with it. Reading the locks one by one may still see one value before and
+/// the other after that instant.
+///
+/// Takes writer locks like any other write, so it panics under the same conditions, e.g.
+/// if a written lock is closed.
+///
+/// 以事务方式对 `f` 读写的锁运行 `f`，并将其写入作为一个整体提交。
+///
+/// `f` 在不持有任何锁的情况下运行，通过 [`Transaction::read`] 读取，通过 [`Transaction::write`]
+/// 或 [`Transaction::store`] 写入。它返回后，按固定的全局顺序获取其涉及的每个锁的写入锁，
+/// 因此事务之间永远不会死锁。如果其中任何一个锁在 `f` 读取之后发生了变化，`f` 会再次运行，
+/// 因此它可能被调用多次，并且应当没有副作用。
+///
+/// 所有写入在同一时刻对读者可见。[`snapshot`](crate::snapshot) 会与之一致地读取多个锁。逐个读取这些锁仍可能
+/// 看到一个值在该时刻之前、另一个值在该时刻之后。
+///
+/// 与其他写入一样获取写入锁，因此在相同条件下会 panic，例如被写入的锁已关闭。
+///
+/// # Example
+///
+/// ```
+/// use lfrlock::{LfrLock, transaction};
+/// use std::collections::HashMap;
+///
+/// let users = LfrLock::new(HashMap::from([(1, "alice".to_string())]));
+/// let by_name = LfrLock::new(HashMap::from([("alice".to_string(), 1)]));
+///
+/// transaction(|tx| {
+///     let old = tx.read(&users)[&1].clone();
+///     tx.write(&users).insert(1, "bob".to_string());
+///     let index = tx.write(&by_name);
+///     index.remove(&old);
+///     index.insert("bob".to_string(), 1);
+/// });
+///
+/// let (users, by_name) = lfrlock::snapshot((&users, &by_name));
+/// assert_eq!(by_name[&users[&1]], 1);
+/// ```
+#[track_caller]
+pub fn transaction<'a, F, U>(mut f: F) -> U
+where
+    F: FnMut(&mut Transaction<'a>) -> U,
+{
+    loop {
+        let mut tx = Transaction {
+            entries: Vec::new(),
+        };
+        let result = f(&mut tx);
+        if tx.commit() {
+            return result;
+        }
+        drop(tx);
+
+        #[cfg(feature = "std")]
+        std::thread::yield_now();
+        #[cfg(not(feature = "std"))]
+        core::hint::spin_loop();
+    }
+}
@@ -0,0 +1,129 @@
+//! Errors of the write operations that give up instead of waiting.
+//!
+//! 放弃而不是等待的写入操作的错误类型。
+
+use core::fmt;
+
+use crate::BacklogFull;
+
+/// Why a non-blocking or timed write did not get the write lock.
+///
+/// Returned by [`LfrLock::try_write`](crate::LfrLock::try_write) and
+/// [`LfrLock::write_timeout`](crate::LfrLock::write_timeout), and carried by
+/// [`TryStoreError`] and [`TryUpdateError`].
+///
+/// 非阻塞或限时写入未能获取写入锁的原因。
+///
+/// 由 [`LfrLock::try_write`](crate::LfrLock::try_write) 和
+/// [`LfrLock::write_timeout`](crate::LfrLock::write_timeout) 返回，并由 [`TryStoreError`] 和
+/// [`TryUpdateError`] 携带。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TryWriteError {
+    /// Another writer holds the write lock.
+    ///
+    /// 另一个写者持有写入锁。
+    WouldBlock,
+    /// The write lock was not released within the timeout.
+    ///
+    /// 写入锁未在超时时间内释放。
+    Timeout,
+    /// The calling thread already holds the write lock of this lock. Only detected with the
+    /// `reentrancy-check` feature, otherwise reported as `WouldBlock` or `Timeout`. Without
+    /// `std`, threads are the contexts identified by `set_context_id`.
+    ///
+    /// 调用线程已经持有此锁的写入锁。仅在启用 `reentrancy-check` feature 时检测，
+    /// 否则报告为 `WouldBlock` 或 `Timeout`。没有 `std` 时，线程指由 `set_context_id` 标识的上下文。
+    Reentrant,
+    /// The lock no longer accepts writes, see [`LfrLock::close`](crate::LfrLock::close).
+    ///
+    /// 锁不再接受写入，参见 [`LfrLock::close`](crate::LfrLock::close)。
+    Closed,
+}
+
+impl fmt::Display for TryWriteError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            TryWriteError::WouldBlock => "the write lock is held by another writer",
+            TryWriteError::Timeout => "timed out waiting for the write lock",
+            TryWriteError::Reentrant => "the write lock is already held by this thread",
+            TryWriteError::Closed => "the lock no longer accepts writes",
+        })
+    }
+}
+
+impl core::error::Error for TryWriteError {}
+
+/// Error returned by [`LfrLock::try_store`](crate::LfrLock::try_store), carrying the
+/// value back.
+///
+/// [`LfrLock::try_store`](crate::LfrLock::try_store) 返回的错误，携带原值返回。
+pub enum TryStoreError<T> {
+    /// The write lock was not acquired.
+    ///
+    /// 未获取到写入锁。
+    Lock(TryWriteError, T),
+    /// The backlog of retired values is full, see
+    /// [`BacklogPolicy::Reject`](crate::BacklogPolicy::Reject).
+    ///
+    /// 退休值积压已满，参见 [`BacklogPolicy::Reject`](crate::BacklogPolicy::Reject)。
+    BacklogFull(BacklogFull<T>),
+}
+
+impl<T> TryStoreError<T> {
+    /// Consume the error and return the value that was not stored.
+    ///
+    /// 消耗该错误并返回未被存储的值。
+    #[inline]
+    pub fn into_inner(self) -> T {
+        match self {
+            TryStoreError::Lock(_, value) => value,
+            TryStoreError::BacklogFull(full) => full.into_inner(),
+        }
+    }
+}
+
+impl<T> fmt::Debug for TryStoreError<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TryStoreError::Lock(error, _) => f.debug_tuple("Lock").field(error).finish(),
+            TryStoreError::BacklogFull(full) => f.debug_tuple("BacklogFull").field(full).finish(),
+        }
+    }
+}
+
+impl<T> fmt::Display for TryStoreError<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TryStoreError::Lock(error, _) => fmt::Display::fmt(error, f),
+            TryStoreError::BacklogFull(full) => fmt::Display::fmt(full, f),
+        }
+    }
+}
+
+impl<T> core::error::Error for TryStoreError<T> {}
+
+/// Error returned by [`LfrLock::try_lock_update`](crate::LfrLock::try_lock_update).
+///
+/// [`LfrLock::try_lock_update`](crate::LfrLock::try_lock_update) 返回的错误。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TryUpdateError<E> {
+    /// The write lock was not acquired, the closure did not run.
+    ///
+    /// 未获取到写入锁，闭包没有运行。
+    Lock(TryWriteError),
+    /// The closure returned an error, the value stays untouched.
+    ///
+    /// 闭包返回了错误，值保持不变。
+    Aborted(E),
+}
+
+impl<E: fmt::Display> fmt::Display for TryUpdateError<E> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TryUpdateError::Lock(error) => fmt::Display::fmt(error, f),
+            TryUpdateError::Aborted(error) => fmt::Display::fmt(error, f),
+        }
+    }
+}
+
+impl<E: fmt::Debug + fmt::Display> core::error::Error for TryUpdateError<E> {}
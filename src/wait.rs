@@ -0,0 +1,172 @@
+//! Blocking waits on committed values.
+//!
+//! A waiting thread registers a thread-unparking `Waker` in the same `WatchList` that async
+//! subscribers use and parks until a commit wakes it. Readers never touch the list, and
+//! commits only pay an atomic load while nobody waits.
+//!
+//! 对已提交值的阻塞等待。
+//!
+//! 等待线程在异步订阅者使用的同一个 `WatchList` 中注册一个唤醒线程的 `Waker`，
+//! 并挂起直到某次提交将其唤醒。读取者从不访问该列表，无人等待时提交只需一次原子加载。
+
+use std::sync::Arc;
+use std::task::{Wake, Waker};
+use std::thread::{self, Thread};
+use std::time::{Duration, Instant};
+use std::vec::Vec;
+
+use lock_api::RawMutex;
+
+use crate::LfrLock;
+use crate::watch::WatchList;
+
+/// Waker that unparks the waiting thread.
+///
+/// 唤醒等待线程的 waker。
+struct ThreadWaker(Thread);
+
+impl Wake for ThreadWaker {
+    #[inline]
+    fn wake(self: Arc<Self>) {
+        self.0.unpark();
+    }
+
+    #[inline]
+    fn wake_by_ref(self: &Arc<Self>) {
+        self.0.unpark();
+    }
+}
+
+/// Create a waker that unparks the current thread.
+///
+/// 创建一个唤醒当前线程的 waker。
+#[inline]
+pub(crate) fn thread_waker() -> Waker {
+    Waker::from(Arc::new(ThreadWaker(thread::current())))
+}
+
+/// Park the current thread until `ready` returns `Some`, or until `deadline` passes.
+///
+/// `ready` is re-checked after every registration, so a commit between the check and parking
+/// is never missed. Spurious wake-ups simply check again.
+///
+/// 挂起当前线程，直到 `ready` 返回 `Some` 或超过 `deadline`。
+///
+/// 每次注册后都会重新检查 `ready`，因此不会错过检查与挂起之间的提交。虚假唤醒只会再次检查。
+pub(crate) fn park_until<R>(
+    watchers: &[&WatchList],
+    deadline: Option<Instant>,
+    mut ready: impl FnMut() -> Option<R>,
+) -> Option<R> {
+    if let Some(result) = ready() {
+        return Some(result);
+    }
+
+    let waker = thread_waker();
+    let mut ids: Vec<Option<usize>> = watchers.iter().map(|_| None).collect();
+
+    let result = loop {
+        // Commits consume registrations, so register again on every round
+        // 提交会消费注册，因此每一轮都重新注册
+        for (watchers, id) in watchers.iter().zip(ids.iter_mut()) {
+            watchers.register(id, &waker);
+        }
+        if let Some(result) = ready() {
+            break Some(result);
+        }
+
+        match deadline {
+            None => thread::park(),
+            Some(deadline) => {
+                let now = Instant::now();
+                if now >= deadline {
+                    break None;
+                }
+                thread::park_timeout(deadline - now);
+            }
+        }
+    };
+
+    for (watchers, id) in watchers.iter().zip(ids.iter_mut()) {
+        watchers.unregister(id);
+    }
+    result
+}
+
+mod sealed {
+    pub trait Sealed {
+        fn watchers(&self) -> &crate::watch::WatchList;
+    }
+}
+
+/// A lock that can be waited on with [`wait_any`].
+///
+/// Implemented for every [`LfrLock`], so locks of different value types can be waited on
+/// together.
+///
+/// 可以通过 [`wait_any`] 等待的锁。
+///
+/// 所有 [`LfrLock`] 都实现了该 trait，因此可以同时等待值类型不同的锁。
+pub trait Watchable: sealed::Sealed {
+    /// Get the version of the current value.
+    ///
+    /// 获取当前值的版本号。
+    fn version(&self) -> usize;
+}
+
+impl<T: 'static, R: RawMutex> sealed::Sealed for LfrLock<T, R> {
+    #[inline]
+    fn watchers(&self) -> &WatchList {
+        &self.shared.watchers
+    }
+}
+
+impl<T: 'static, R: RawMutex> Watchable for LfrLock<T, R> {
+    #[inline]
+    fn version(&self) -> usize {
+        LfrLock::version(self)
+    }
+}
+
+/// Block until any of `locks` commits a version other than the one paired with it.
+///
+/// Returns the index of the first lock found changed.
+///
+/// 阻塞直到 `locks` 中的任意一个提交了与其配对版本号不同的版本。
+///
+/// 返回第一个被发现已变更的锁的索引。
+///
+/// # Example
+///
+/// ```
+/// use lfrlock::{LfrLock, wait_any};
+///
+/// let flags = LfrLock::new(false);
+/// let shards = LfrLock::new(Vec::<u32>::new());
+/// let (flags_version, shards_version) = (flags.version(), shards.version());
+///
+/// shards.store(vec![1, 2]);
+/// assert_eq!(wait_any(&[(&flags, flags_version), (&shards, shards_version)]), 1);
+/// ```
+pub fn wait_any(locks: &[(&dyn Watchable, usize)]) -> usize {
+    wait_any_deadline(locks, None).expect("waiting without a deadline never times out")
+}
+
+/// Like [`wait_any`], but gives up after `timeout` and returns `None`.
+///
+/// 与 [`wait_any`] 相同，但在 `timeout` 之后放弃并返回 `None`。
+pub fn wait_any_timeout(locks: &[(&dyn Watchable, usize)], timeout: Duration) -> Option<usize> {
+    wait_any_deadline(locks, Instant::now().checked_add(timeout))
+}
+
+fn wait_any_deadline(
+    locks: &[(&dyn Watchable, usize)],
+    deadline: Option<Instant>,
+) -> Option<usize> {
+    let watchers: Vec<&WatchList> = locks.iter().map(|(lock, _)| lock.watchers()).collect();
+    park_until(&watchers, deadline, || {
+        locks
+            .iter()
+            .position(|(lock, since)| lock.version() != *since)
+    })
+}
@@ -0,0 +1,252 @@
+//! Change notification for subscribers.
+//!
+//! Every commit wakes all registered wakers. A `Subscriber` remembers the last version it saw
+//! and only reports a change when a newer version has been committed, so intermediate values
+//! may be skipped ("latest value wins").
+//!
+//! 订阅者的变更通知。
+//!
+//! 每次提交都会唤醒所有已注册的 waker。`Subscriber` 记录它看到的最后一个版本，
+//! 仅在提交了更新的版本时才报告变更，因此中间值可能被跳过（"最新值优先"）。
+
+use core::future::poll_fn;
+use core::task::{Context, Poll, Waker};
+
+#[cfg(feature = "std")]
+use std::vec::Vec;
+
+#[cfg(not(feature = "std"))]
+use alloc::vec::Vec;
+
+use lock_api::RawMutex;
+
+use crate::lock_impl::Mutex;
+use crate::sync::{AtomicUsize, Ordering, fence};
+use crate::{DefaultRawMutex, LfrLock, ReadGuard};
+
+/// Wakers waiting for the next commit.
+///
+/// `pub` only so that the sealed `Watchable` trait can name it; the module is private.
+///
+/// 等待下一次提交的 waker 列表。
+///
+/// 仅为了让密封的 `Watchable` trait 能够引用它而设为 `pub`；所在模块是私有的。
+pub struct WatchList {
+    /// Number of registered wakers, lets commits skip the list when nobody watches.
+    ///
+    /// 已注册的 waker 数量，使提交在无人观察时跳过列表。
+    len: AtomicUsize,
+    entries: Mutex<Entries>,
+}
+
+struct Entries {
+    wakers: Vec<(usize, Waker)>,
+    next_id: usize,
+}
+
+impl WatchList {
+    #[inline]
+    pub(crate) fn new() -> Self {
+        WatchList {
+            len: AtomicUsize::new(0),
+            entries: Mutex::new(Entries {
+                wakers: Vec::new(),
+                next_id: 0,
+            }),
+        }
+    }
+
+    /// Register `waker` to be woken by the next commit.
+    ///
+    /// `id` identifies the registration across calls; pass the same slot again to refresh it.
+    /// The caller must re-check the version afterwards to not miss a commit in between.
+    ///
+    /// 注册 `waker`，使其被下一次提交唤醒。
+    ///
+    /// `id` 用于在多次调用之间标识该注册；再次传入同一个槽位即可刷新。
+    /// 调用者之后必须重新检查版本，以免错过期间发生的提交。
+    pub(crate) fn register(&self, id: &mut Option<usize>, waker: &Waker) {
+        let mut entries = self.entries.lock();
+        let registered = id.and_then(|id| entries.wakers.iter_mut().find(|(i, _)| *i == id));
+        match registered {
+            Some((_, registered)) => {
+                if !registered.will_wake(waker) {
+                    *registered = waker.clone();
+                }
+            }
+            None => {
+                // Keep the id of a registration that was consumed by a commit
+                // 保留被提交消费掉的注册的 id
+                let new_id = id.unwrap_or(entries.next_id);
+                if id.is_none() {
+                    entries.next_id = new_id.wrapping_add(1);
+                }
+                entries.wakers.push((new_id, waker.clone()));
+                self.len.fetch_add(1, Ordering::SeqCst);
+                *id = Some(new_id);
+            }
+        }
+        drop(entries);
+
+        // Pairs with the fence in `notify_all`
+        // 与 `notify_all` 中的屏障配对
+        fence(Ordering::SeqCst);
+    }
+
+    /// Remove a registration, if it is still present.
+    ///
+    /// 移除注册（如果仍然存在）。
+    pub(crate) fn unregister(&self, id: &mut Option<usize>) {
+        let Some(id) = id.take() else {
+            return;
+        };
+        let mut entries = self.entries.lock();
+        if let Some(index) = entries.wakers.iter().position(|(i, _)| *i == id) {
+            entries.wakers.swap_remove(index);
+            self.len.fetch_sub(1, Ordering::SeqCst);
+        }
+    }
+
+    /// Wake and remove every registered waker.
+    ///
+    /// Must be called after the new value has been stored.
+    ///
+    /// 唤醒并移除所有已注册的 waker。
+    ///
+    /// 必须在新值存储之后调用。
+    #[inline]
+    pub(crate) fn notify_all(&self) {
+        // Either the watcher sees the new value on its re-check, or we see its registration
+        // 要么观察者在重新检查时看到新值，要么我们看到它的注册
+        fence(Ordering::SeqCst);
+        if self.len.load(Ordering::Relaxed) == 0 {
+            return;
+        }
+
+        let wakers = {
+            let mut entries = self.entries.lock();
+            self.len.store(0, Ordering::SeqCst);
+            core::mem::take(&mut entries.wakers)
+        };
+        for (_, waker) in wakers {
+            waker.wake();
+        }
+    }
+}
+
+/// Subscriber - Receives the latest value each time a new one is committed
+///
+/// Created by [`LfrLock::subscribe`]. Owns its own lock handle, so it can be moved into
+/// another thread or task. Like a watch channel, only the latest value is observed:
+/// several commits between two polls are reported as one change.
+///
+/// 订阅者 - 每次提交新值时接收最新值
+///
+/// 由 [`LfrLock::subscribe`] 创建。它拥有自己的锁句柄，因此可以移动到其他线程或任务中。
+/// 与 watch 通道类似，只会观察到最新值：两次轮询之间的多次提交会被报告为一次变更。
+pub struct Subscriber<T: 'static, R: RawMutex = DefaultRawMutex> {
+    lock: LfrLock<T, R>,
+    last_version: usize,
+    id: Option<usize>,
+}
+
+impl<T: 'static, R: RawMutex> Subscriber<T, R> {
+    #[inline]
+    pub(crate) fn new(lock: LfrLock<T, R>) -> Self {
+        let last_version = lock.version();
+        Subscriber {
+            lock,
+            last_version,
+            id: None,
+        }
+    }
+
+    /// Get the version of the value this subscriber has seen last.
+    ///
+    /// 获取此订阅者最后看到的值的版本号。
+    #[inline]
+    pub fn last_version(&self) -> usize {
+        self.last_version
+    }
+
+    /// Check whether a value newer than the last seen one has been committed.
+    ///
+    /// 检查是否已提交了比最后看到的值更新的值。
+    #[inline]
+    pub fn has_changed(&self) -> bool {
+        self.lock.version() != self.last_version
+    }
+
+    /// Read the current value without marking it as seen.
+    ///
+    /// 读取当前值，但不将其标记为已看到。
+    #[inline]
+    pub fn borrow(&self) -> ReadGuard<'_, T> {
+        self.lock.read()
+    }
+
+    /// Read the current value and mark it as seen.
+    ///
+    /// 读取当前值并将其标记为已看到。
+    #[inline]
+    pub fn borrow_and_update(&mut self) -> ReadGuard<'_, T> {
+        let guard = self.lock.read();
+        self.last_version = guard.version();
+        guard
+    }
+
+    /// Wait until a newer value is committed, then return it and mark it as seen.
+    ///
+    /// Returns immediately if a change happened since the last seen value.
+    ///
+    /// 等待直到提交了更新的值，然后返回该值并将其标记为已看到。
+    ///
+    /// 如果自最后看到的值以来已经发生了变更，则立即返回。
+    pub async fn changed(&mut self) -> ReadGuard<'_, T> {
+        poll_fn(|cx| self.poll_change(cx)).await;
+        self.borrow_and_update()
+    }
+
+    /// Poll for a change without marking it as seen.
+    ///
+    /// 轮询变更，但不将其标记为已看到。
+    pub fn poll_change(&mut self, cx: &mut Context<'_>) -> Poll<()> {
+        if self.has_changed() {
+            return Poll::Ready(());
+        }
+
+        self.lock.shared.watchers.register(&mut self.id, cx.waker());
+
+        // Re-check after registering so that a commit in between is not missed
+        // 注册后重新检查，避免错过期间发生的提交
+        if self.has_changed() {
+            Poll::Ready(())
+        } else {
+            Poll::Pending
+        }
+    }
+}
+
+impl<T: 'static, R: RawMutex> Drop for Subscriber<T, R> {
+    #[inline]
+    fn drop(&mut self) {
+        self.lock.shared.watchers.unregister(&mut self.id);
+    }
+}
+
+#[cfg(feature = "stream")]
+impl<T: Clone + 'static, R: RawMutex> futures_core::Stream for Subscriber<T, R> {
+    /// A snapshot of each newly committed value.
+    ///
+    /// 每个新提交值的快照。
+    type Item = T;
+
+    fn poll_next(
+        self: core::pin::Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Self::Item>> {
+        let this = self.get_mut();
+        this.poll_change(cx)
+            .map(|()| Some(this.borrow_and_update().cloned()))
+    }
+}
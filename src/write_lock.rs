@@ -0,0 +1,572 @@
+//! Writer Mutex with an async-capable wait queue.
+//!
+//! An uncontended acquisition goes straight to the underlying `RawMutex`. Otherwise, blocking
+//! and async acquisitions alike join one FIFO queue. While anyone waits in it, newcomers queue
+//! up behind instead of trying the mutex, and only the waiter at the front may take it. Each
+//! unlock wakes the front waiter. Dropping a pending future removes it from the queue and
+//! wakes the waiter that moves up to the front.
+//!
+//! 支持异步的写入 Mutex。
+//!
+//! 无竞争的获取直接使用底层的 `RawMutex`。否则，阻塞获取和异步获取都会加入同一个 FIFO 队列。
+//! 只要队列中有人等待，新来者就排到后面，而不是尝试获取 mutex，并且只有队首的等待者可以获取它。
+//! 每次解锁都会唤醒队首的等待者。丢弃挂起的 future 会将其从队列中移除，并唤醒移到队首的等待者。
+
+use core::future::Future;
+use core::mem::ManuallyDrop;
+use core::ops::{Deref, DerefMut};
+use core::pin::Pin;
+use core::task::{Context, Poll, Waker};
+
+#[cfg(feature = "std")]
+use std::time::{Duration, Instant};
+
+#[cfg(feature = "std")]
+use std::collections::VecDeque;
+
+#[cfg(not(feature = "std"))]
+use alloc::collections::VecDeque;
+
+use lock_api::RawMutex;
+
+use crate::deadlock::Node;
+use crate::lock_impl::Mutex;
+use crate::sync::{AtomicBool, AtomicUsize, Ordering, fence};
+
+/// Writer Mutex that can be acquired both blocking and asynchronously.
+///
+/// 可以阻塞或异步获取的写入 Mutex。
+pub(crate) struct WriteLock<T, R: RawMutex> {
+    mutex: lock_api::Mutex<R, T>,
+    queue: WaitQueue,
+    poison: Poison,
+    owner: Owner,
+    node: Node,
+}
+
+impl<T, R: RawMutex> WriteLock<T, R> {
+    #[inline]
+    pub(crate) fn new(t: T, poisoning: bool, name: Option<&str>) -> Self {
+        WriteLock {
+            mutex: lock_api::Mutex::new(t),
+            poison: Poison {
+                enabled: poisoning,
+                poisoned: AtomicBool::new(false),
+            },
+            queue: WaitQueue {
+                len: AtomicUsize::new(0),
+                front: AtomicUsize::new(NO_WAITER),
+                waiters: Mutex::new(Waiters {
+                    entries: VecDeque::new(),
+                    next_id: 0,
+                }),
+            },
+            owner: Owner::new(),
+            node: Node::new(name),
+        }
+    }
+
+    /// Acquire the lock, blocking the current thread.
+    ///
+    /// # Panics
+    ///
+    /// Panics if reentrancy detection is enabled and this thread already holds the lock, or
+    /// if deadlock detection is enabled and taking it breaks the order of earlier
+    /// acquisitions.
+    ///
+    /// 获取锁，阻塞当前线程。
+    ///
+    /// # Panics
+    ///
+    /// 如果启用了重入检测且本线程已持有该锁，或者启用了死锁检测且获取该锁违反了先前获取的顺序，则 panic。
+    #[inline]
+    #[track_caller]
+    pub(crate) fn lock(&self) -> WriteLockGuard<'_, T, R> {
+        // Checked before trying, so an inversion is reported even when it does not block
+        // 在尝试之前检查，因此即使没有阻塞也会报告顺序颠倒
+        self.node.check_order();
+        if let Some(guard) = self.try_lock_untracked() {
+            return self.held(guard);
+        }
+
+        // Waiting for ourselves would never end
+        // 等待自己永远不会结束
+        if self.owner.is_current() {
+            panic!("reentrant write: this thread already holds the write lock of this LfrLock");
+        }
+
+        // Wait for our turn, then block on the mutex itself, which only a newcomer that came
+        // before us may still hold
+        // 等到轮到自己，再阻塞在 mutex 本身上，此时只有比我们先到的新来者可能仍持有它
+        let mut future = self.lock_async();
+        future.wait_for_turn();
+        let guard = future.acquired(self.wrap(self.mutex.lock()));
+        self.held(guard)
+    }
+
+    /// Try to acquire the lock without blocking.
+    ///
+    /// Fails while anyone waits in the queue. Failing never waits, so the lock order is not
+    /// checked, but a success is recorded.
+    ///
+    /// 尝试在不阻塞的情况下获取锁。
+    ///
+    /// 只要队列中有人等待就会失败。失败时从不等待，因此不检查加锁顺序，但成功时会被记录。
+    #[inline]
+    #[track_caller]
+    pub(crate) fn try_lock(&self) -> Option<WriteLockGuard<'_, T, R>> {
+        self.try_lock_untracked().map(|guard| self.held(guard))
+    }
+
+    /// Try to acquire the lock for a task that may move to another thread before
+    /// releasing it.
+    ///
+    /// 为可能在释放锁之前移动到其他线程的任务尝试获取锁。
+    #[inline]
+    fn try_lock_untracked(&self) -> Option<WriteLockGuard<'_, T, R>> {
+        // Never overtake a queued waiter
+        // 永远不要超过排队的等待者
+        if self.queue.len.load(Ordering::SeqCst) > 0 {
+            return None;
+        }
+        self.mutex.try_lock().map(|guard| self.wrap(guard))
+    }
+
+    /// Record that the current thread holds the lock.
+    ///
+    /// 记录当前线程持有该锁。
+    #[inline]
+    #[track_caller]
+    fn held<'a>(&'a self, guard: WriteLockGuard<'a, T, R>) -> WriteLockGuard<'a, T, R> {
+        self.owner.set_current();
+        self.node.acquired();
+        guard
+    }
+
+    /// Check whether this thread holds the lock through a blocking acquisition.
+    ///
+    /// Always `false` while reentrancy detection is disabled.
+    ///
+    /// 检查本线程是否通过阻塞获取持有该锁。
+    ///
+    /// 禁用重入检测时始终为 `false`。
+    #[inline]
+    pub(crate) fn is_held_by_current_thread(&self) -> bool {
+        self.owner.is_current()
+    }
+
+    #[inline]
+    fn wrap<'a>(&'a self, guard: lock_api::MutexGuard<'a, R, T>) -> WriteLockGuard<'a, T, R> {
+        WriteLockGuard {
+            guard: ManuallyDrop::new(guard),
+            queue: &self.queue,
+            poison: &self.poison,
+            owner: &self.owner,
+            node: &self.node,
+        }
+    }
+
+    /// Acquire the lock asynchronously.
+    ///
+    /// 异步获取锁。
+    #[inline]
+    pub(crate) fn lock_async(&self) -> LockFuture<'_, T, R> {
+        LockFuture {
+            lock: self,
+            id: None,
+        }
+    }
+
+    /// Acquire the lock, giving up once `timeout` has passed.
+    ///
+    /// Parks in the async wait queue, so the thread is woken by the next unlock instead of
+    /// polling.
+    ///
+    /// 获取锁，超过 `timeout` 后放弃。
+    ///
+    /// 在异步等待队列中挂起，因此线程由下一次解锁唤醒，而不是轮询。
+    #[cfg(feature = "std")]
+    #[track_caller]
+    pub(crate) fn lock_timeout(&self, timeout: Duration) -> Option<WriteLockGuard<'_, T, R>> {
+        if let Some(guard) = self.try_lock() {
+            return Some(guard);
+        }
+        if self.owner.is_current() {
+            return None;
+        }
+
+        let deadline = Instant::now().checked_add(timeout);
+        let waker = crate::wait::thread_waker();
+        let mut cx = Context::from_waker(&waker);
+        let mut future = self.lock_async();
+        loop {
+            if let Poll::Ready(guard) = Pin::new(&mut future).poll(&mut cx) {
+                return Some(self.held(guard));
+            }
+            match deadline {
+                Some(deadline) => {
+                    let now = Instant::now();
+                    if now >= deadline {
+                        return None;
+                    }
+                    std::thread::park_timeout(deadline - now);
+                }
+                None => std::thread::park(),
+            }
+        }
+    }
+
+    /// Check whether a writer panicked while holding the lock.
+    ///
+    /// 检查是否有写者在持有锁时 panic。
+    #[inline]
+    pub(crate) fn is_poisoned(&self) -> bool {
+        self.poison.poisoned.load(Ordering::Acquire)
+    }
+
+    /// Clear the poisoned state.
+    ///
+    /// 清除中毒状态。
+    #[inline]
+    pub(crate) fn clear_poison(&self) {
+        self.poison.poisoned.store(false, Ordering::Release);
+    }
+}
+
+/// Poison state of a `WriteLock`.
+///
+/// Only tracked when enabled, and only with `std`, where unwinding can be detected.
+///
+/// `WriteLock` 的中毒状态。
+///
+/// 仅在启用时跟踪，且仅在能检测展开的 `std` 环境下有效。
+struct Poison {
+    #[cfg_attr(not(feature = "std"), allow(dead_code))]
+    enabled: bool,
+    poisoned: AtomicBool,
+}
+
+/// Context holding a `WriteLock`, recorded to detect reentrant writes.
+///
+/// Only tracked with the `reentrancy-check` feature, otherwise it has no size and every
+/// check is `false`. Contexts are told apart by `reentrancy::current`: the thread with
+/// `std`, the function given to `set_context_id` otherwise. Async acquisitions are not
+/// recorded, since the task holding the lock may resume on another thread.
+///
+/// 持有 `WriteLock` 的上下文，记录下来用于检测重入写入。
+///
+/// 仅在启用 `reentrancy-check` feature 时跟踪，否则它不占空间且所有检查都为 `false`。
+/// 上下文由 `reentrancy::current` 区分：`std` 下为线程，否则为传给 `set_context_id` 的函数。
+/// 异步获取不会被记录，因为持有锁的任务可能在其他线程上恢复执行。
+struct Owner {
+    /// Identity of the owning context, 0 while unknown.
+    ///
+    /// 持有者上下文的标识，未知时为 0。
+    #[cfg(feature = "reentrancy-check")]
+    context: AtomicUsize,
+}
+
+#[cfg(feature = "reentrancy-check")]
+impl Owner {
+    #[inline]
+    fn new() -> Self {
+        Owner {
+            context: AtomicUsize::new(0),
+        }
+    }
+
+    #[inline]
+    fn is_current(&self) -> bool {
+        // Only this context ever stores its own identity, so a stale value never matches
+        // 只有本上下文会存储自己的标识，因此过时的值永远不会匹配
+        let current = crate::reentrancy::current();
+        current != 0 && self.context.load(Ordering::Relaxed) == current
+    }
+
+    #[inline]
+    fn set_current(&self) {
+        self.context
+            .store(crate::reentrancy::current(), Ordering::Relaxed);
+    }
+
+    #[inline]
+    fn clear(&self) {
+        self.context.store(0, Ordering::Relaxed);
+    }
+}
+
+#[cfg(not(feature = "reentrancy-check"))]
+impl Owner {
+    #[inline]
+    fn new() -> Self {
+        Owner {}
+    }
+
+    #[inline]
+    fn is_current(&self) -> bool {
+        false
+    }
+
+    #[inline]
+    fn set_current(&self) {}
+
+    #[inline]
+    fn clear(&self) {}
+}
+
+/// FIFO queue of async waiters.
+///
+/// 异步等待者的 FIFO 队列。
+struct WaitQueue {
+    /// Number of registered waiters, lets unlock skip the queue when nobody waits.
+    ///
+    /// 已注册的等待者数量，使解锁在无人等待时跳过队列。
+    len: AtomicUsize,
+    /// Id of the front waiter, lets blocked writers wait for their turn without taking
+    /// `waiters`.
+    ///
+    /// 队首等待者的 id，使阻塞的写者无需获取 `waiters` 即可等待轮到自己。
+    front: AtomicUsize,
+    waiters: Mutex<Waiters>,
+}
+
+/// Value of `WaitQueue::front` while the queue is empty.
+///
+/// 队列为空时 `WaitQueue::front` 的值。
+const NO_WAITER: usize = usize::MAX;
+
+struct Waiters {
+    entries: VecDeque<Waiter>,
+    next_id: usize,
+}
+
+struct Waiter {
+    id: usize,
+    waker: Waker,
+}
+
+impl WaitQueue {
+    /// Register a new waiter or refresh the waker of an existing one, returning whether it
+    /// is at the front.
+    ///
+    /// 注册新的等待者，或刷新已有等待者的 waker，返回它是否位于队首。
+    fn register(&self, id: &mut Option<usize>, waker: &Waker) -> bool {
+        let mut waiters = self.waiters.lock();
+        match *id {
+            Some(id) => {
+                if let Some(waiter) = waiters.entries.iter_mut().find(|w| w.id == id)
+                    && !waiter.waker.will_wake(waker)
+                {
+                    waiter.waker = waker.clone();
+                }
+                waiters.entries.front().is_some_and(|w| w.id == id)
+            }
+            None => {
+                let new_id = waiters.next_id;
+                waiters.next_id = match new_id.wrapping_add(1) {
+                    NO_WAITER => 0,
+                    next => next,
+                };
+                waiters.entries.push_back(Waiter {
+                    id: new_id,
+                    waker: waker.clone(),
+                });
+                self.len.fetch_add(1, Ordering::SeqCst);
+                *id = Some(new_id);
+                let first = waiters.entries.len() == 1;
+                if first {
+                    self.front.store(new_id, Ordering::Release);
+                }
+                first
+            }
+        }
+    }
+
+    /// Remove a waiter, returning whether it was at the front.
+    ///
+    /// 移除等待者，返回它是否位于队首。
+    fn remove(&self, id: usize) -> bool {
+        let mut waiters = self.waiters.lock();
+        match waiters.entries.iter().position(|w| w.id == id) {
+            Some(index) => {
+                self.len.fetch_sub(1, Ordering::SeqCst);
+                waiters.entries.remove(index);
+                if index == 0 {
+                    let front = waiters.entries.front().map_or(NO_WAITER, |w| w.id);
+                    self.front.store(front, Ordering::Release);
+                }
+                index == 0
+            }
+            None => false,
+        }
+    }
+
+    /// Whether the waiter `id` is at the front.
+    ///
+    /// 等待者 `id` 是否位于队首。
+    #[inline]
+    fn is_front(&self, id: usize) -> bool {
+        self.front.load(Ordering::Acquire) == id
+    }
+
+    /// Wake the waiter at the front, the only one allowed to take the lock.
+    ///
+    /// 唤醒队首的等待者，只有它可以获取锁。
+    fn notify_front(&self) {
+        let waker = {
+            let waiters = self.waiters.lock();
+            waiters.entries.front().map(|waiter| waiter.waker.clone())
+        };
+        if let Some(waker) = waker {
+            waker.wake();
+        }
+    }
+}
+
+/// Guard of a `WriteLock`, wakes the front waiter on drop.
+///
+/// `WriteLock` 的守卫，在 drop 时唤醒队首的等待者。
+#[must_use]
+pub(crate) struct WriteLockGuard<'a, T, R: RawMutex> {
+    guard: ManuallyDrop<lock_api::MutexGuard<'a, R, T>>,
+    queue: &'a WaitQueue,
+    #[cfg_attr(not(feature = "std"), allow(dead_code))]
+    poison: &'a Poison,
+    owner: &'a Owner,
+    node: &'a Node,
+}
+
+impl<'a, T, R: RawMutex> Deref for WriteLockGuard<'a, T, R> {
+    type Target = T;
+
+    #[inline]
+    fn deref(&self) -> &T {
+        &self.guard
+    }
+}
+
+impl<'a, T, R: RawMutex> DerefMut for WriteLockGuard<'a, T, R> {
+    #[inline]
+    fn deref_mut(&mut self) -> &mut T {
+        &mut self.guard
+    }
+}
+
+impl<'a, T, R: RawMutex> Drop for WriteLockGuard<'a, T, R> {
+    #[inline]
+    fn drop(&mut self) {
+        // Mark the lock poisoned before releasing it, so the next writer sees it
+        // 在释放锁之前标记中毒，使下一个写者能看到
+        #[cfg(feature = "std")]
+        if self.poison.enabled && std::thread::panicking() {
+            self.poison.poisoned.store(true, Ordering::Release);
+        }
+
+        // Unlock first, then wake a waiter so that its retry can succeed
+        // 先解锁，再唤醒等待者，使其重试能够成功
+        self.owner.clear();
+        self.node.released();
+        unsafe { ManuallyDrop::drop(&mut self.guard) };
+
+        // Pairs with the fence in `LockFuture::poll`: either the waiter sees the lock
+        // released, or we see the waiter registered.
+        // 与 `LockFuture::poll` 中的屏障配对：要么等待者看到锁已释放，要么我们看到等待者已注册。
+        fence(Ordering::SeqCst);
+        if self.queue.len.load(Ordering::Relaxed) > 0 {
+            self.queue.notify_front();
+        }
+    }
+}
+
+/// Future returned by `WriteLock::lock_async`.
+///
+/// Cancellation-safe: dropping it before completion never holds the lock.
+///
+/// `WriteLock::lock_async` 返回的 future。
+///
+/// 取消安全：在完成前丢弃它永远不会持有锁。
+pub(crate) struct LockFuture<'a, T, R: RawMutex> {
+    lock: &'a WriteLock<T, R>,
+    id: Option<usize>,
+}
+
+impl<'a, T, R: RawMutex> LockFuture<'a, T, R> {
+    /// Leave the queue, without waking the next waiter since the lock is now held.
+    ///
+    /// 离开队列，不唤醒下一个等待者，因为锁现在已被持有。
+    #[inline]
+    fn acquired(&mut self, guard: WriteLockGuard<'a, T, R>) -> WriteLockGuard<'a, T, R> {
+        if let Some(id) = self.id.take() {
+            self.lock.queue.remove(id);
+        }
+        guard
+    }
+
+    /// Queue up and block the current thread until this waiter is at the front.
+    ///
+    /// Without `std` there is no thread to park, so it spins instead. Each handoff then waits
+    /// for the front waiter to run, which is slow when threads outnumber cores.
+    ///
+    /// 排队并阻塞当前线程，直到此等待者位于队首。
+    ///
+    /// 没有 `std` 时没有可挂起的线程，因此改为自旋。此时每次交接都要等待队首的等待者运行，
+    /// 在线程数多于核心数时较慢。
+    fn wait_for_turn(&mut self) {
+        #[cfg(feature = "std")]
+        let waker = &crate::wait::thread_waker();
+        #[cfg(not(feature = "std"))]
+        let waker = Waker::noop();
+
+        if self.lock.queue.register(&mut self.id, waker) {
+            return;
+        }
+
+        // The waker stays registered, so waiting only needs to watch `front`
+        // waker 保持注册状态，因此等待时只需观察 `front`
+        let Some(id) = self.id else { return };
+        while !self.lock.queue.is_front(id) {
+            #[cfg(feature = "std")]
+            std::thread::park();
+            #[cfg(not(feature = "std"))]
+            core::hint::spin_loop();
+        }
+    }
+}
+
+impl<'a, T, R: RawMutex> Future for LockFuture<'a, T, R> {
+    type Output = WriteLockGuard<'a, T, R>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let this = &mut *self;
+        if this.id.is_none()
+            && let Some(guard) = this.lock.try_lock_untracked()
+        {
+            return Poll::Ready(guard);
+        }
+
+        if !this.lock.queue.register(&mut this.id, cx.waker()) {
+            return Poll::Pending;
+        }
+
+        // Retry after registering so that an unlock in between is not missed
+        // 注册后重试，避免错过期间发生的解锁
+        fence(Ordering::SeqCst);
+        match this.lock.mutex.try_lock() {
+            Some(guard) => Poll::Ready(this.acquired(this.lock.wrap(guard))),
+            None => Poll::Pending,
+        }
+    }
+}
+
+impl<'a, T, R: RawMutex> Drop for LockFuture<'a, T, R> {
+    #[inline]
+    fn drop(&mut self) {
+        // The next waiter moves up to the front, and may have missed the unlock meant for us
+        // 下一个等待者移到队首，它可能错过了本应唤醒我们的解锁
+        if let Some(id) = self.id.take()
+            && self.lock.queue.remove(id)
+        {
+            self.lock.queue.notify_front();
+        }
+    }
+}
@@ -0,0 +1,110 @@
+mod common;
+
+use common::{Drops, socket};
+use lfrlock::{AdaptiveConfig, LfrLock, Strategy};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::thread;
+
+/// Switches on every commit: always read-preferred after a write-preferred window and the
+/// other way round.
+///
+/// 每次提交都会切换：写优先窗口之后总是读优先，反之亦然。
+fn flipping() -> Strategy {
+    Strategy::Adaptive(
+        AdaptiveConfig::new()
+            .window(1)
+            .read_preferred_above(0)
+            .write_preferred_below(usize::MAX),
+    )
+}
+
+#[test]
+fn test_adaptive_follows_workload() {
+    let config = AdaptiveConfig::new()
+        .window(4)
+        .read_preferred_above(500)
+        .write_preferred_below(50);
+    let lock = LfrLock::builder()
+        .strategy(Strategy::Adaptive(config))
+        .build(0usize);
+    assert_eq!(lock.strategy(), Strategy::Adaptive(config));
+    assert_eq!(lock.active_strategy(), Strategy::WritePreferred);
+
+    // Read-heavy phase
+    // 读多阶段
+    for i in 1..=4 {
+        for _ in 0..1000 {
+            assert_eq!(*lock.read(), i - 1);
+        }
+        lock.store(i);
+    }
+    assert_eq!(lock.active_strategy(), Strategy::ReadPreferred);
+
+    // Write-heavy phase
+    // 写多阶段
+    for i in 5..=8 {
+        lock.store(i);
+    }
+    assert_eq!(lock.active_strategy(), Strategy::WritePreferred);
+    assert_eq!(*lock.read(), 8);
+    assert_eq!(lock.version(), 8);
+}
+
+#[test]
+fn test_adaptive_concurrent_switching() {
+    let lock = LfrLock::builder().strategy(flipping()).build(0usize);
+    let factory = lock.factory();
+    let done = Arc::new(AtomicBool::new(false));
+
+    let readers: Vec<_> = (0..4)
+        .map(|_| {
+            let factory = factory.clone();
+            let done = done.clone();
+            thread::spawn(move || {
+                let lock = factory.create();
+                let mut last = 0;
+                while !done.load(Ordering::Relaxed) {
+                    let guard = lock.read();
+                    // Values never go backwards, even across backends
+                    // 即使跨越后端，值也永远不会倒退
+                    assert_eq!(*guard, guard.version());
+                    assert!(guard.version() >= last);
+                    last = guard.version();
+                }
+            })
+        })
+        .collect();
+
+    for i in 1..=2000 {
+        lock.store(i);
+    }
+    done.store(true, Ordering::Relaxed);
+    for reader in readers {
+        reader.join().unwrap();
+    }
+    assert_eq!(*lock.read(), 2000);
+}
+
+#[test]
+fn test_adaptive_reclamation() {
+    let drops = Drops::new();
+    let lock = LfrLock::builder()
+        .strategy(flipping())
+        .build(socket(&drops));
+
+    for _ in 0..5 {
+        lock.store(socket(&drops));
+    }
+
+    // The dormant backend never keeps a replaced value alive
+    // 休眠的后端永远不会使被替换的值保持存活
+    lock.synchronize();
+    assert_eq!(drops.count(), 5);
+    assert_eq!(lock.backlog(), 0);
+
+    let retired = lock.swap_retired(socket(&drops));
+    lock.store(socket(&drops));
+    drop(retired.into_inner());
+    assert_eq!(drops.count(), 7);
+}
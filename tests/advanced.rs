@@ -37,7 +37,7 @@ fn test_try_write() {
     // 测试 try_write - 没有其他写者时应该成功
     {
         let guard = lock.try_write();
-        assert!(guard.is_some());
+        assert!(guard.is_ok());
         let mut guard = guard.unwrap();
         guard.value = 42;
     }
@@ -98,3 +98,17 @@ fn test_chained_operations() {
     assert_eq!(squared, 25);
     assert_eq!(lock.get().value, 5); // unchanged
 }
+
+#[test]
+fn test_rcu() {
+    let lock = LfrLock::new(Data { value: 1 });
+
+    // Test rcu - returns guard to the replaced value
+    // 测试 rcu - 返回被替换值的守卫
+    let old_guard = lock.rcu(|old| Data {
+        value: old.value + 1,
+    });
+    assert_eq!(old_guard.value, 1);
+    assert_eq!(lock.read().value, 2);
+    assert_eq!(lock.version(), 1);
+}
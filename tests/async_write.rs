@@ -0,0 +1,281 @@
+mod common;
+
+use common::block_on;
+use lfrlock::LfrLock;
+use std::future::Future;
+use std::pin::{Pin, pin};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::task::{Context, Poll, Wake, Waker};
+use std::thread;
+use std::time::Duration;
+
+#[derive(Debug, Clone, PartialEq)]
+struct Data {
+    value: i32,
+}
+
+/// Waker that counts how often it was woken.
+///
+/// 记录被唤醒次数的 waker。
+struct CountingWaker(AtomicUsize);
+
+impl Wake for CountingWaker {
+    fn wake(self: Arc<Self>) {
+        self.0.fetch_add(1, Ordering::SeqCst);
+    }
+}
+
+fn counting_waker() -> (Arc<CountingWaker>, Waker) {
+    let counter = Arc::new(CountingWaker(AtomicUsize::new(0)));
+    let waker = Waker::from(counter.clone());
+    (counter, waker)
+}
+
+#[test]
+fn test_async_write_uncontended() {
+    let lock = LfrLock::new(Data { value: 0 });
+
+    block_on(async {
+        {
+            let mut guard = lock.write_async().await;
+            guard.value = 1;
+        }
+        lock.store_async(Data { value: 2 }).await;
+        lock.update_async(|d| Data {
+            value: d.value * 10,
+        })
+        .await;
+    });
+
+    assert_eq!(lock.read().value, 20);
+    assert_eq!(lock.version(), 3);
+}
+
+#[test]
+fn test_async_write_waits_for_blocking_writer() {
+    let lock = LfrLock::new(Data { value: 0 });
+    let factory = lock.factory();
+
+    let guard = lock.write();
+
+    let handle = thread::spawn(move || {
+        let lock = factory.create();
+        block_on(lock.update_async(|d| Data { value: d.value + 1 }));
+    });
+
+    // Async writer must wait until the blocking writer commits
+    // 异步写者必须等待阻塞写者提交
+    thread::sleep(Duration::from_millis(50));
+    assert_eq!(lock.read().value, 0);
+    drop(guard);
+
+    handle.join().unwrap();
+    // The untouched blocking guard committed nothing
+    // 未被修改的阻塞守卫没有提交任何内容
+    assert_eq!(lock.read().value, 1);
+    assert_eq!(lock.version(), 1);
+}
+
+#[test]
+fn test_async_writers_concurrent() {
+    let lock = LfrLock::new(Data { value: 0 });
+    let factory = lock.factory();
+
+    let mut handles = vec![];
+    for _ in 0..4 {
+        let factory = factory.clone();
+        handles.push(thread::spawn(move || {
+            let lock = factory.create();
+            block_on(async {
+                for _ in 0..25 {
+                    lock.update_async(|d| Data { value: d.value + 1 }).await;
+                    let mut guard = lock.write_async().await;
+                    guard.value += 1;
+                }
+            });
+        }));
+    }
+
+    for handle in handles {
+        handle.join().unwrap();
+    }
+
+    assert_eq!(lock.read().value, 200);
+}
+
+#[test]
+fn test_cancelled_write_never_commits() {
+    let lock = LfrLock::new(Data { value: 0 });
+    let (_, waker) = counting_waker();
+    let mut cx = Context::from_waker(&waker);
+
+    let guard = lock.write();
+    {
+        let mut future = pin!(lock.store_async(Data { value: 99 }));
+        assert!(future.as_mut().poll(&mut cx).is_pending());
+    } // future dropped before acquisition / future 在获取前被丢弃
+    drop(guard);
+
+    assert_eq!(lock.read().value, 0);
+    assert_eq!(lock.version(), 0);
+
+    // Lock is free again after the cancelled future
+    // 被取消的 future 之后锁再次空闲
+    assert!(lock.try_write().is_ok());
+}
+
+#[test]
+fn test_async_waiters_fifo() {
+    let lock = LfrLock::new(Data { value: 0 });
+    let (first_counter, first_waker) = counting_waker();
+    let (second_counter, second_waker) = counting_waker();
+
+    let guard = lock.write();
+    let mut first = pin!(lock.write_async());
+    let mut second = pin!(lock.write_async());
+    assert!(
+        first
+            .as_mut()
+            .poll(&mut Context::from_waker(&first_waker))
+            .is_pending()
+    );
+    assert!(
+        second
+            .as_mut()
+            .poll(&mut Context::from_waker(&second_waker))
+            .is_pending()
+    );
+
+    // Unlock wakes only the oldest waiter
+    // 解锁只唤醒最早的等待者
+    drop(guard);
+    assert_eq!(first_counter.0.load(Ordering::SeqCst), 1);
+    assert_eq!(second_counter.0.load(Ordering::SeqCst), 0);
+
+    let Poll::Ready(mut first_guard) = first.as_mut().poll(&mut Context::from_waker(&first_waker))
+    else {
+        panic!("first waiter should acquire the lock");
+    };
+    first_guard.value = 1;
+    drop(first_guard);
+    assert_eq!(second_counter.0.load(Ordering::SeqCst), 1);
+
+    let Poll::Ready(second_guard) = second
+        .as_mut()
+        .poll(&mut Context::from_waker(&second_waker))
+    else {
+        panic!("second waiter should acquire the lock");
+    };
+    assert_eq!(second_guard.value, 1);
+}
+
+#[test]
+fn test_waiters_acquire_in_queue_order() {
+    let lock = LfrLock::new(Data { value: 0 });
+    let wakers: Vec<_> = (0..3).map(|_| counting_waker()).collect();
+    let poll = |future: Pin<&mut _>, i: usize| {
+        Future::poll(future, &mut Context::from_waker(&wakers[i].1))
+    };
+
+    let guard = lock.write();
+    let mut first = pin!(lock.write_async());
+    let mut second = pin!(lock.write_async());
+    let mut third = pin!(lock.write_async());
+    assert!(poll(first.as_mut(), 0).is_pending());
+    assert!(poll(second.as_mut(), 1).is_pending());
+    assert!(poll(third.as_mut(), 2).is_pending());
+
+    // A blocking writer arriving later queues up behind them
+    // 之后到来的阻塞写者排在它们后面
+    let blocking = {
+        let lock = lock.clone();
+        thread::spawn(move || {
+            let mut guard = lock.write();
+            assert_eq!(guard.value, 3);
+            guard.value = 4;
+        })
+    };
+    thread::sleep(Duration::from_millis(50));
+
+    // Neither a newcomer nor a later waiter overtakes the front one
+    // 新来者和靠后的等待者都不会超过队首的等待者
+    drop(guard);
+    assert!(lock.try_write().is_err());
+    assert!(poll(third.as_mut(), 2).is_pending());
+    assert!(poll(second.as_mut(), 1).is_pending());
+
+    let Poll::Ready(mut guard) = poll(first.as_mut(), 0) else {
+        panic!("first waiter should acquire the lock");
+    };
+    guard.value = 1;
+    drop(guard);
+    assert_eq!(wakers[1].0.0.load(Ordering::SeqCst), 1);
+    assert!(poll(third.as_mut(), 2).is_pending());
+
+    let Poll::Ready(mut guard) = poll(second.as_mut(), 1) else {
+        panic!("second waiter should acquire the lock");
+    };
+    guard.value = 2;
+    drop(guard);
+
+    let Poll::Ready(mut guard) = poll(third.as_mut(), 2) else {
+        panic!("third waiter should acquire the lock");
+    };
+    assert_eq!(guard.value, 2);
+    guard.value = 3;
+    drop(guard);
+
+    blocking.join().unwrap();
+    assert_eq!(lock.read().value, 4);
+}
+
+#[test]
+fn test_cancelled_waiter_passes_wakeup_on() {
+    let lock = LfrLock::new(Data { value: 0 });
+    let (_, first_waker) = counting_waker();
+    let (second_counter, second_waker) = counting_waker();
+
+    let guard = lock.write();
+    let mut second = pin!(lock.write_async());
+    {
+        let mut first = pin!(lock.write_async());
+        assert!(
+            first
+                .as_mut()
+                .poll(&mut Context::from_waker(&first_waker))
+                .is_pending()
+        );
+        assert!(
+            second
+                .as_mut()
+                .poll(&mut Context::from_waker(&second_waker))
+                .is_pending()
+        );
+
+        drop(guard);
+        assert_eq!(second_counter.0.load(Ordering::SeqCst), 0);
+    } // notified first waiter dropped / 已被通知的第一个等待者被丢弃
+
+    // The wake-up is handed on to the next waiter
+    // 唤醒被传递给下一个等待者
+    assert_eq!(second_counter.0.load(Ordering::SeqCst), 1);
+    assert!(
+        second
+            .as_mut()
+            .poll(&mut Context::from_waker(&second_waker))
+            .is_ready()
+    );
+}
+
+#[test]
+fn test_async_futures_are_send() {
+    fn assert_send<F: Future + Send>(_: F) {}
+
+    // Futures only borrow the shared writer state, so they can move between executor threads
+    // future 只借用共享的写入状态，因此可以在执行器线程之间移动
+    let lock = LfrLock::new(Data { value: 0 });
+    assert_send(lock.write_async());
+    assert_send(lock.store_async(Data { value: 1 }));
+    assert_send(lock.update_async(|d| d.clone()));
+}
@@ -0,0 +1,104 @@
+use lfrlock::{BacklogPolicy, LfrLock, TryStoreError};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, mpsc};
+use std::thread;
+use std::time::{Duration, Instant};
+
+#[test]
+fn test_backlog_grows_with_pinned_reader() {
+    let lock = LfrLock::new(0);
+    assert_eq!(lock.backlog(), 0);
+
+    let guard = lock.read();
+    for i in 1..=10 {
+        lock.store(i);
+    }
+    assert_eq!(lock.backlog(), 10);
+    drop(guard);
+
+    // Only the copies used to advance the epoch are left
+    // 只剩下用于推进纪元的副本
+    lock.flush_retired();
+    assert!(lock.backlog() <= 2);
+}
+
+#[test]
+fn test_try_store_reject() {
+    let lock = LfrLock::builder()
+        .max_backlog(4, BacklogPolicy::Reject)
+        .build(0);
+    let guard = lock.read();
+
+    for i in 1..=4 {
+        assert_eq!(lock.try_store(i).unwrap(), i);
+    }
+    let Err(TryStoreError::BacklogFull(err)) = lock.try_store(5) else {
+        panic!("expected a full backlog");
+    };
+    assert_eq!(err.backlog(), 4);
+    assert_eq!(err.into_inner(), 5);
+    assert_eq!(lock.version(), 4);
+
+    // Other commit paths still go through
+    // 其他提交路径仍然照常进行
+    lock.store(5);
+    assert_eq!(lock.version(), 5);
+
+    drop(guard);
+    assert_eq!(lock.try_store(6).unwrap(), 6);
+}
+
+#[test]
+fn test_backlog_callback() {
+    let calls = Arc::new(AtomicUsize::new(0));
+    let seen = calls.clone();
+    let lock = LfrLock::builder()
+        .max_backlog(
+            3,
+            BacklogPolicy::callback(move |backlog| {
+                assert!(backlog >= 3);
+                seen.fetch_add(1, Ordering::SeqCst);
+            }),
+        )
+        .build(0);
+    let guard = lock.read();
+
+    for i in 1..=5 {
+        lock.store(i);
+    }
+
+    // Called for the fourth and fifth store, both of which still commit
+    // 第四次和第五次存储时调用，且两次都照常提交
+    assert_eq!(calls.load(Ordering::SeqCst), 2);
+    assert_eq!(*lock.read(), 5);
+    drop(guard);
+}
+
+#[test]
+fn test_backlog_block() {
+    let lock = LfrLock::builder()
+        .max_backlog(3, BacklogPolicy::Block)
+        .build(0);
+    let factory = lock.factory();
+    let (pinned_tx, pinned_rx) = mpsc::channel();
+
+    let handle = thread::spawn(move || {
+        let lock = factory.create();
+        let _guard = lock.read();
+        pinned_tx.send(()).unwrap();
+        thread::sleep(Duration::from_millis(50));
+    });
+
+    pinned_rx.recv().unwrap();
+    let start = Instant::now();
+    for i in 1..=10 {
+        lock.store(i);
+        assert!(lock.backlog() <= 3);
+    }
+
+    // The writer had to wait for the reader to let go
+    // 写者必须等待读者释放
+    assert!(start.elapsed() >= Duration::from_millis(20));
+    assert_eq!(*lock.read(), 10);
+    handle.join().unwrap();
+}
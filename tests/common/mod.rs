@@ -0,0 +1,95 @@
+//! Fixtures shared by the integration tests.
+//!
+//! 集成测试共用的测试夹具。
+
+#![allow(dead_code)]
+
+use std::future::Future;
+use std::pin::pin;
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll, Wake, Waker};
+use std::thread::{self, Thread};
+use std::time::{Duration, Instant};
+
+/// Waker that unparks the thread running `block_on`.
+///
+/// 唤醒运行 `block_on` 的线程的 waker。
+struct ThreadWaker(Thread);
+
+impl Wake for ThreadWaker {
+    fn wake(self: Arc<Self>) {
+        self.0.unpark();
+    }
+}
+
+/// Minimal executor so the tests do not depend on an async runtime.
+///
+/// 最小执行器，使测试不依赖异步运行时。
+pub fn block_on<F: Future>(future: F) -> F::Output {
+    let mut future = pin!(future);
+    let waker = Waker::from(Arc::new(ThreadWaker(thread::current())));
+    let mut cx = Context::from_waker(&waker);
+    loop {
+        if let Poll::Ready(output) = future.as_mut().poll(&mut cx) {
+            return output;
+        }
+        thread::park();
+    }
+}
+
+/// Record of the drops of [`Socket`] values, by the name of the thread each happened on.
+///
+/// [`Socket`] 值的 drop 记录，按每次 drop 所在线程的名称记录。
+#[derive(Default)]
+pub struct Drops(Mutex<Vec<Option<String>>>);
+
+impl Drops {
+    pub fn new() -> Arc<Self> {
+        Arc::default()
+    }
+
+    /// Number of values dropped so far.
+    ///
+    /// 目前已 drop 的值的数量。
+    pub fn count(&self) -> usize {
+        self.0.lock().unwrap().len()
+    }
+
+    /// Whether every drop happened on the thread called `name`.
+    ///
+    /// 是否每次 drop 都发生在名为 `name` 的线程上。
+    pub fn all_on(&self, name: &str) -> bool {
+        self.0
+            .lock()
+            .unwrap()
+            .iter()
+            .all(|thread| thread.as_deref() == Some(name))
+    }
+
+    /// Wait until `count` values were dropped, e.g. by a background thread.
+    ///
+    /// 等待 `count` 个值被 drop，例如由后台线程 drop。
+    pub fn wait_for(&self, count: usize) {
+        let deadline = Instant::now() + Duration::from_secs(5);
+        while self.count() < count {
+            assert!(Instant::now() < deadline, "values were not dropped in time");
+            thread::sleep(Duration::from_millis(1));
+        }
+    }
+}
+
+/// Value that records its drop in [`Drops`].
+///
+/// 在 [`Drops`] 中记录自身 drop 的值。
+pub struct Socket(Arc<Drops>);
+
+impl Drop for Socket {
+    fn drop(&mut self) {
+        let name = thread::current().name().map(String::from);
+        self.0.0.lock().unwrap().push(name);
+    }
+}
+
+pub fn socket(drops: &Arc<Drops>) -> Socket {
+    Socket(drops.clone())
+}
@@ -85,3 +85,39 @@ fn test_multiple_readers_and_writers() {
     let data = lock.read();
     assert_eq!(data.value, 100);
 }
+
+#[test]
+fn test_rcu_multiple_writers() {
+    let lock = LfrLock::new(Data { value: 0 });
+    let factory = lock.factory();
+
+    let mut handles = vec![];
+
+    for _ in 0..4 {
+        let factory = factory.clone();
+        let handle = thread::spawn(move || {
+            let lock = factory.create();
+            let mut calls = 0;
+            for _ in 0..50 {
+                // Closure runs outside the writer Mutex and may be retried
+                // 闭包在写入 Mutex 之外运行，可能被重试
+                let old = lock.rcu(|old_data| {
+                    calls += 1;
+                    Data {
+                        value: old_data.value + 1,
+                    }
+                });
+                assert!(old.value >= 0 && old.value < 200);
+            }
+            calls
+        });
+        handles.push(handle);
+    }
+
+    let total_calls: i32 = handles.into_iter().map(|h| h.join().unwrap()).sum();
+
+    // No increment is lost, retries only add extra closure calls
+    // 没有增量丢失，重试只会增加额外的闭包调用
+    assert_eq!(lock.read().value, 200);
+    assert!(total_calls >= 200);
+}
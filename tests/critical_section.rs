@@ -0,0 +1,81 @@
+#![cfg(feature = "critical-section")]
+
+// Uses the `std` implementation of `critical-section`, where a critical section is a
+// process-wide lock that the owning thread may enter again
+// 使用 `critical-section` 的 `std` 实现，其中临界区是进程范围的锁，持有它的线程可以再次进入
+
+use lfrlock::{CriticalSectionRawMutex, LfrLock, LfrLockFactory};
+use std::thread;
+
+#[test]
+fn test_critical_section_writes() {
+    let factory: LfrLockFactory<Vec<usize>, CriticalSectionRawMutex> = LfrLockFactory::builder()
+        .raw_mutex()
+        .build_factory(Vec::new());
+
+    let handles: Vec<_> = (0..4)
+        .map(|i| {
+            let factory = factory.clone();
+            thread::spawn(move || {
+                let lock = factory.create();
+                for j in 0..100 {
+                    lock.write().push(i * 100 + j);
+                    assert!(lock.read().len() <= 400);
+                }
+            })
+        })
+        .collect();
+    for handle in handles {
+        handle.join().unwrap();
+    }
+
+    let lock = factory.create();
+    let mut values = lock.read().cloned();
+    values.sort_unstable();
+    assert_eq!(values, (0..400).collect::<Vec<_>>());
+}
+
+#[test]
+fn test_critical_section_try_write() {
+    let lock = LfrLock::builder()
+        .raw_mutex::<CriticalSectionRawMutex>()
+        .build(0);
+
+    let mut guard = lock.write();
+    assert!(lock.try_write().is_err());
+    *guard = 1;
+    drop(guard);
+
+    // Reads never enter the critical section
+    // 读取永远不会进入临界区
+    critical_section::with(|_| assert_eq!(*lock.read(), 1));
+    assert!(lock.try_write().is_ok());
+}
+
+#[test]
+fn test_critical_section_try_write_does_not_wait() {
+    let lock = LfrLock::builder()
+        .raw_mutex::<CriticalSectionRawMutex>()
+        .build(0);
+    let _guard = lock.write();
+
+    // Entering the critical section would wait for this thread to leave it
+    // 进入临界区会等待本线程离开它
+    let other = lock.clone();
+    thread::spawn(move || assert!(other.try_write().is_err()))
+        .join()
+        .unwrap();
+}
+
+// Caught by the reentrancy check when it is enabled, by the mutex itself otherwise
+// 启用重入检测时由其捕获，否则由互斥锁自身捕获
+#[test]
+#[should_panic]
+fn test_critical_section_relock_panics() {
+    let lock = LfrLock::builder()
+        .raw_mutex::<CriticalSectionRawMutex>()
+        .build(0);
+
+    let _guard = lock.write();
+    lock.store(1);
+}
@@ -0,0 +1,153 @@
+#![cfg(feature = "deadlock-detection")]
+
+use lfrlock::{LfrLock, transaction};
+use std::panic::{AssertUnwindSafe, catch_unwind};
+use std::thread;
+
+/// Run `f` and return its panic message.
+///
+/// 运行 `f` 并返回其 panic 信息。
+fn panic_message(f: impl FnOnce()) -> String {
+    let payload = catch_unwind(AssertUnwindSafe(f)).expect_err("expected a panic");
+    match payload.downcast::<String>() {
+        Ok(message) => *message,
+        Err(payload) => payload.downcast_ref::<&str>().unwrap().to_string(),
+    }
+}
+
+#[test]
+fn test_inversion_reported_without_hang() {
+    let routing = LfrLock::builder().name("routing").build(0);
+    let acl = LfrLock::builder().name("acl").build(0);
+
+    // Another thread takes routing then acl, and is long gone afterwards
+    // 另一个线程先获取 routing 再获取 acl，之后早已结束
+    let (other_routing, other_acl) = (routing.clone(), acl.clone());
+    thread::spawn(move || {
+        let _routing = other_routing.write();
+        other_acl.store(1);
+    })
+    .join()
+    .unwrap();
+
+    let message = panic_message(|| {
+        let _acl = acl.write();
+        routing.store(1);
+    });
+    assert!(message.starts_with("lock order inversion"), "{message}");
+    assert!(message.contains("\"routing\""), "{message}");
+    assert!(message.contains("\"acl\""), "{message}");
+    // Sites of the four acquisitions, all in this file
+    // 四次获取的位置，都在本文件中
+    assert_eq!(
+        message.matches("tests/deadlock.rs:").count(),
+        4,
+        "{message}"
+    );
+
+    // The rejected acquisition did not happen, and the lock is still usable in order
+    // 被拒绝的获取没有发生，按顺序使用时锁仍然可用
+    assert_eq!(*routing.read(), 0);
+    let _routing = routing.write();
+    acl.store(2);
+}
+
+#[test]
+fn test_longer_cycle_reported() {
+    let a = LfrLock::builder().name("a").build(0);
+    let b = LfrLock::builder().name("b").build(0);
+    let c = LfrLock::new(0);
+
+    {
+        let _a = a.write();
+        b.store(1);
+    }
+    {
+        let _b = b.write();
+        c.store(1);
+    }
+    let message = panic_message(|| {
+        let _c = c.write();
+        a.store(1);
+    });
+    assert!(message.contains("LfrLock#"), "{message}");
+    assert!(message.contains("-> \"a\" -> \"b\" ->"), "{message}");
+    assert_eq!(message.matches("earlier").count(), 2, "{message}");
+}
+
+#[test]
+fn test_consistent_order_passes() {
+    let a = LfrLock::new(0);
+    let b = LfrLock::new(0);
+    let handles: Vec<_> = (0..4)
+        .map(|_| {
+            let (a, b) = (a.clone(), b.clone());
+            thread::spawn(move || {
+                for _ in 0..100 {
+                    let mut a = a.write();
+                    let mut b = b.write();
+                    *a += 1;
+                    *b += 1;
+                }
+            })
+        })
+        .collect();
+    for handle in handles {
+        handle.join().unwrap();
+    }
+    assert_eq!((*a.read(), *b.read()), (400, 400));
+}
+
+#[test]
+fn test_try_write_is_not_checked() {
+    let a = LfrLock::new(0);
+    let b = LfrLock::new(0);
+    {
+        let _a = a.write();
+        b.store(1);
+    }
+
+    // Trying never waits, so it cannot deadlock
+    // 尝试从不等待，因此不会死锁
+    let _b = b.write();
+    let mut a = a.try_write().unwrap();
+    *a = 1;
+}
+
+#[test]
+fn test_guards_released_out_of_order() {
+    let a = LfrLock::new(0);
+    let b = LfrLock::new(0);
+    let c = LfrLock::new(0);
+
+    let guard_a = a.write();
+    let guard_b = b.write();
+    drop(guard_a);
+    drop(guard_b);
+
+    // Nothing is held anymore, so c -> a adds no edge from a or b
+    // 已不再持有任何锁，因此 c -> a 不会添加来自 a 或 b 的边
+    let _c = c.write();
+    a.store(1);
+    b.store(1);
+}
+
+#[test]
+fn test_transactions_take_part() {
+    let a = LfrLock::builder().name("a").build(0);
+    let b = LfrLock::builder().name("b").build(0);
+
+    {
+        let _a = a.write();
+        transaction(|tx| tx.store(&b, 1));
+    }
+    let message = panic_message(|| {
+        let _b = b.write();
+        a.store(1);
+    });
+    assert_eq!(
+        message.matches("tests/deadlock.rs:").count(),
+        4,
+        "{message}"
+    );
+}
@@ -0,0 +1,95 @@
+use lfrlock::LfrLock;
+use std::sync::{Arc, Barrier};
+use std::thread;
+
+const THREADS: usize = 8;
+const ITERATIONS: usize = 500;
+
+/// Run `op` concurrently on every thread and collect what each call returned.
+///
+/// 在每个线程上并发运行 `op`，并收集每次调用的返回值。
+fn run_contended<F>(lock: &LfrLock<usize>, op: F) -> Vec<(usize, usize)>
+where
+    F: Fn(&LfrLock<usize>) -> (usize, usize) + Send + Sync + Copy + 'static,
+{
+    let factory = lock.factory();
+    let barrier = Arc::new(Barrier::new(THREADS));
+
+    let handles: Vec<_> = (0..THREADS)
+        .map(|_| {
+            let factory = factory.clone();
+            let barrier = barrier.clone();
+            thread::spawn(move || {
+                let lock = factory.create();
+                barrier.wait();
+                (0..ITERATIONS).map(|_| op(&lock)).collect::<Vec<_>>()
+            })
+        })
+        .collect();
+
+    let mut results: Vec<_> = handles
+        .into_iter()
+        .flat_map(|handle| handle.join().unwrap())
+        .collect();
+    results.sort_unstable();
+    results
+}
+
+#[test]
+fn test_fetch_and_update_returns_replaced_value() {
+    let lock = LfrLock::new(0usize);
+
+    let results = run_contended(&lock, |lock| {
+        let old = lock.fetch_and_update(|v| v + 1);
+        (*old, old.version())
+    });
+
+    // Every value was replaced exactly once, so the old values are 0..N without gaps
+    // 每个值都恰好被替换一次，因此旧值是连续的 0..N
+    let expected: Vec<_> = (0..THREADS * ITERATIONS).map(|v| (v, v)).collect();
+    assert_eq!(results, expected);
+    assert_eq!(*lock.read(), THREADS * ITERATIONS);
+}
+
+#[test]
+fn test_update_and_fetch_returns_installed_value() {
+    let lock = LfrLock::new(0usize);
+
+    let results = run_contended(&lock, |lock| {
+        let new = lock.update_and_fetch(|v| v + 1);
+        (*new, new.version())
+    });
+
+    // Every installed value is seen by exactly the call that produced it
+    // 每个写入的值都恰好被产生它的那次调用看到
+    let expected: Vec<_> = (1..=THREADS * ITERATIONS).map(|v| (v, v)).collect();
+    assert_eq!(results, expected);
+}
+
+#[test]
+fn test_before_after_pairs_match() {
+    let lock = LfrLock::new(0usize);
+
+    // Mix both operations with other writers to stress the interleaving
+    // 将两种操作与其他写者混合，以加大交错的压力
+    let results = run_contended(&lock, |lock| {
+        lock.update(|v| v + 1);
+        let old = lock.fetch_and_update(|v| v + 1);
+        let new = lock.update_and_fetch(|v| v + 1);
+        assert_eq!(old.version(), *old);
+        assert_eq!(new.version(), *new);
+        (old.version(), new.version())
+    });
+
+    // No two calls replaced or installed the same version
+    // 没有两次调用替换或写入了同一个版本
+    let mut olds: Vec<_> = results.iter().map(|&(old, _)| old).collect();
+    let mut news: Vec<_> = results.iter().map(|&(_, new)| new).collect();
+    olds.sort_unstable();
+    olds.dedup();
+    news.sort_unstable();
+    news.dedup();
+    assert_eq!(olds.len(), results.len());
+    assert_eq!(news.len(), results.len());
+    assert!(results.iter().all(|&(old, new)| old < new));
+}
@@ -0,0 +1,148 @@
+use lfrlock::{LfrLock, MappedReadGuard, MappedWriteGuard, ReadGuard, WriteGuard};
+#[cfg(feature = "std")]
+use std::panic::{AssertUnwindSafe, catch_unwind};
+
+#[derive(Debug, Clone, PartialEq)]
+struct Config {
+    name: String,
+    server: Server,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct Server {
+    port: u16,
+    hosts: Vec<String>,
+}
+
+fn config() -> Config {
+    Config {
+        name: "api".into(),
+        server: Server {
+            port: 80,
+            hosts: vec!["a".into(), "b".into()],
+        },
+    }
+}
+
+/// A module that only knows about its own part of the config.
+///
+/// 只了解配置中属于自己那部分的模块。
+fn first_host(server: MappedReadGuard<'_, Config, Server>) -> Option<String> {
+    server.hosts.first().cloned()
+}
+
+#[test]
+fn test_read_map() {
+    let lock = LfrLock::new(config());
+
+    let server = ReadGuard::map(lock.read(), |config| &config.server);
+    assert_eq!(server.port, 80);
+    assert_eq!(first_host(server.clone()), Some("a".into()));
+
+    // Unsized targets, mapped in steps
+    // 非固定大小的目标，分步映射
+    let host = MappedReadGuard::map(server, |server| server.hosts[1].as_str());
+    assert_eq!(&*host, "b");
+    assert_eq!(
+        format!("{host:?}"),
+        r#"MappedReadGuard { value: "b", version: 0 }"#
+    );
+
+    // Keeps the whole value alive across stores
+    // 跨越存储操作保持整个值存活
+    lock.store(Config {
+        name: "new".into(),
+        ..config()
+    });
+    assert!(host.is_stale());
+    assert_eq!(host.version(), 0);
+    assert_eq!(&*host, "b");
+}
+
+#[test]
+fn test_read_try_map() {
+    let lock = LfrLock::new(config());
+
+    let guard = ReadGuard::try_map(lock.read(), |config| config.server.hosts.get(5)).unwrap_err();
+    assert_eq!(guard.name, "api");
+
+    let host = ReadGuard::try_map(guard, |config| config.server.hosts.first()).unwrap();
+    assert_eq!(*host, "a");
+    let host = MappedReadGuard::try_map(host, |host| host.strip_prefix('x')).unwrap_err();
+    assert_eq!(*host, "a");
+}
+
+#[test]
+fn test_write_map() {
+    let lock = LfrLock::new(config());
+
+    let mut port = WriteGuard::map(lock.write(), |config| &mut config.server.port);
+    *port = 8080;
+    assert_eq!(lock.read().server.port, 80);
+    drop(port);
+
+    let current = lock.read();
+    assert_eq!(current.server.port, 8080);
+    assert_eq!(current.name, "api");
+    assert_eq!(current.version(), 1);
+}
+
+#[test]
+fn test_write_map_keeps_earlier_changes() {
+    let lock = LfrLock::new(config());
+
+    let mut guard = lock.write();
+    guard.name = "renamed".into();
+    let server = WriteGuard::map(guard, |config| &mut config.server);
+    let mut hosts = MappedWriteGuard::map(server, |server| &mut server.hosts);
+    hosts.push("c".into());
+    assert_eq!(hosts.commit(), 1);
+
+    let current = lock.read();
+    assert_eq!(current.name, "renamed");
+    assert_eq!(current.server.hosts, ["a", "b", "c"]);
+}
+
+#[test]
+fn test_write_map_abort() {
+    let lock = LfrLock::new(config());
+
+    let mut port = WriteGuard::map(lock.write(), |config| &mut config.server.port);
+    *port = 1;
+    port.abort();
+    assert_eq!(*lock.read(), config());
+    assert_eq!(lock.version(), 0);
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn test_write_map_panic() {
+    let lock = LfrLock::new(config());
+
+    let result = catch_unwind(AssertUnwindSafe(|| {
+        let mut port = WriteGuard::map(lock.write(), |config| &mut config.server.port);
+        *port = 2;
+        panic!("mutation failed halfway");
+    }));
+    assert!(result.is_err());
+    assert_eq!(*lock.read(), config());
+    assert_eq!(lock.version(), 0);
+
+    // The writer lock was released
+    // 写入锁已被释放
+    assert!(lock.try_write().is_ok());
+}
+
+#[test]
+fn test_write_try_map() {
+    let lock = LfrLock::new(config());
+
+    let guard = WriteGuard::try_map(lock.write(), |config| config.server.hosts.get_mut(5));
+    let guard = guard.unwrap_err();
+    assert_eq!(guard.name, "api");
+
+    let mut host = WriteGuard::try_map(guard, |config| config.server.hosts.last_mut()).unwrap();
+    host.push('!');
+    drop(host);
+    assert_eq!(lock.read().server.hosts, ["a", "b!"]);
+}
@@ -0,0 +1,98 @@
+use lfrlock::LfrLock;
+use std::panic::{AssertUnwindSafe, catch_unwind};
+#[cfg(feature = "std")]
+use std::thread;
+
+#[derive(Debug, Clone, PartialEq)]
+struct Data {
+    value: i32,
+}
+
+fn panic_while_writing(lock: &LfrLock<Data>) {
+    let result = catch_unwind(AssertUnwindSafe(|| {
+        let mut guard = lock.write();
+        guard.value = -1;
+        panic!("writer failed");
+    }));
+    assert!(result.is_err());
+}
+
+#[test]
+fn test_not_poisoned_by_default() {
+    let lock = LfrLock::new(Data { value: 1 });
+
+    panic_while_writing(&lock);
+
+    // Default behaviour ignores the panic
+    // 默认行为忽略 panic
+    assert!(!lock.is_poisoned());
+    assert!(lock.read_checked().is_ok());
+    assert!(lock.write_checked().is_ok());
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn test_poisoned_by_panicking_writer() {
+    let lock = LfrLock::builder().poisoning(true).build(Data { value: 1 });
+    assert!(!lock.is_poisoned());
+
+    panic_while_writing(&lock);
+    assert!(lock.is_poisoned());
+
+    // The error carries the last good value
+    // 错误携带最后一个正常值
+    let err = lock.read_checked().unwrap_err();
+    assert_eq!(err.get_ref().value, 1);
+    assert_eq!(err.into_inner().version(), 0);
+
+    // Unchecked accessors keep working
+    // 未检查的访问方法继续工作
+    assert_eq!(lock.read().value, 1);
+
+    // The writer can repair the value, then clear the poison
+    // 写者可以修复该值，然后清除中毒状态
+    let mut guard = lock.write_checked().unwrap_err().into_inner();
+    guard.value = 2;
+    drop(guard);
+    lock.clear_poison();
+
+    assert!(!lock.is_poisoned());
+    assert_eq!(lock.read_checked().unwrap().value, 2);
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn test_closure_panic_poisons() {
+    let lock = LfrLock::builder().poisoning(true).build(Data { value: 1 });
+
+    let result = catch_unwind(AssertUnwindSafe(|| {
+        lock.update(|_| panic!("update failed"));
+    }));
+    assert!(result.is_err());
+
+    assert!(lock.is_poisoned());
+    assert_eq!(lock.read().value, 1);
+    assert_eq!(lock.version(), 0);
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn test_poison_shared_across_threads() {
+    let factory = LfrLock::builder()
+        .poisoning(true)
+        .build_factory(Data { value: 1 });
+
+    let writer = factory.clone();
+    thread::spawn(move || {
+        let lock = writer.create();
+        let mut guard = lock.write();
+        guard.value = -1;
+        panic!("writer thread failed");
+    })
+    .join()
+    .unwrap_err();
+
+    let lock = factory.create();
+    assert!(lock.is_poisoned());
+    assert_eq!(lock.read_checked().unwrap_err().get_ref().value, 1);
+}
@@ -0,0 +1,158 @@
+use lfrlock::lock_api::{GuardSend, RawMutex};
+use lfrlock::{DefaultRawMutex, LfrLock, LfrLockFactory};
+use std::hint::spin_loop;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::thread;
+
+/// Spin lock with exponential backoff.
+///
+/// 带指数退避的自旋锁。
+struct BackoffSpinLock(AtomicBool);
+
+unsafe impl RawMutex for BackoffSpinLock {
+    #[allow(clippy::declare_interior_mutable_const)]
+    const INIT: Self = BackoffSpinLock(AtomicBool::new(false));
+
+    type GuardMarker = GuardSend;
+
+    fn lock(&self) {
+        let mut spins = 1;
+        while !self.try_lock() {
+            for _ in 0..spins {
+                spin_loop();
+            }
+            if spins < 64 {
+                spins *= 2;
+            } else {
+                thread::yield_now();
+            }
+        }
+    }
+
+    fn try_lock(&self) -> bool {
+        self.0
+            .compare_exchange(false, true, Ordering::Acquire, Ordering::Relaxed)
+            .is_ok()
+    }
+
+    unsafe fn unlock(&self) {
+        self.0.store(false, Ordering::Release);
+    }
+}
+
+/// Counts every acquisition, to check which mutex a lock uses.
+///
+/// 统计每次获取，用于检查锁使用了哪个互斥锁。
+struct CountingMutex(DefaultRawMutex);
+
+static COUNTING_LOCKS: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl RawMutex for CountingMutex {
+    #[allow(clippy::declare_interior_mutable_const)]
+    const INIT: Self = CountingMutex(DefaultRawMutex::INIT);
+
+    type GuardMarker = GuardSend;
+
+    fn lock(&self) {
+        COUNTING_LOCKS.fetch_add(1, Ordering::Relaxed);
+        RawMutex::lock(&self.0);
+    }
+
+    fn try_lock(&self) -> bool {
+        // Trait calls, `spin::Mutex` has inherent methods of the same names
+        // 使用 trait 调用，`spin::Mutex` 有同名的固有方法
+        let locked = RawMutex::try_lock(&self.0);
+        if locked {
+            COUNTING_LOCKS.fetch_add(1, Ordering::Relaxed);
+        }
+        locked
+    }
+
+    unsafe fn unlock(&self) {
+        unsafe { RawMutex::unlock(&self.0) };
+    }
+}
+
+#[test]
+fn test_custom_raw_mutex() {
+    let lock: LfrLock<Vec<u32>, CountingMutex> = LfrLock::builder().raw_mutex().build(Vec::new());
+    let before = COUNTING_LOCKS.load(Ordering::Relaxed);
+
+    lock.store(vec![1]);
+    lock.update(|v| {
+        let mut v = v.clone();
+        v.push(2);
+        v
+    });
+    lock.write().push(3);
+    assert!(COUNTING_LOCKS.load(Ordering::Relaxed) - before >= 3);
+
+    // Readers never take the writer mutex
+    // 读者永远不会获取写入互斥锁
+    let before = COUNTING_LOCKS.load(Ordering::Relaxed);
+    for _ in 0..100 {
+        assert_eq!(*lock.read(), vec![1, 2, 3]);
+    }
+    assert_eq!(COUNTING_LOCKS.load(Ordering::Relaxed), before);
+}
+
+#[test]
+fn test_try_write_with_custom_mutex() {
+    let lock = LfrLock::builder().raw_mutex::<BackoffSpinLock>().build(0);
+
+    let guard = lock.write();
+    assert!(lock.try_write().is_err());
+    drop(guard);
+
+    let mut guard = lock.try_write().unwrap();
+    *guard = 1;
+    drop(guard);
+    assert_eq!(*lock.read(), 1);
+}
+
+#[test]
+fn test_concurrent_writers_with_custom_mutex() {
+    let factory: LfrLockFactory<usize, BackoffSpinLock> =
+        LfrLockFactory::builder().raw_mutex().build_factory(0);
+
+    let handles: Vec<_> = (0..8)
+        .map(|_| {
+            let factory = factory.clone();
+            thread::spawn(move || {
+                let lock = factory.create();
+                for _ in 0..1000 {
+                    lock.update(|v| v + 1);
+                }
+            })
+        })
+        .collect();
+    for handle in handles {
+        handle.join().unwrap();
+    }
+
+    assert_eq!(*factory.create().read(), 8000);
+}
+
+#[test]
+fn test_default_raw_mutex_contention() {
+    // Writers hold the lock long enough for the others to sleep on it
+    // 写者持有锁的时间足够长，使其他写者在其上休眠
+    let lock = LfrLock::new(0usize);
+    let handles: Vec<_> = (0..8)
+        .map(|_| {
+            let lock = lock.clone();
+            thread::spawn(move || {
+                for _ in 0..200 {
+                    let mut guard = lock.write();
+                    *guard += 1;
+                    thread::yield_now();
+                }
+            })
+        })
+        .collect();
+    for handle in handles {
+        handle.join().unwrap();
+    }
+
+    assert_eq!(*lock.read(), 1600);
+}
@@ -0,0 +1,205 @@
+mod common;
+
+use common::{Drops, Socket, socket};
+use lfrlock::lock_api::{GuardSend, RawMutex};
+use lfrlock::{DefaultRawMutex, LfrLock, LfrLockFactory};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::mpsc;
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+
+#[test]
+fn test_flush_retired() {
+    let drops = Drops::new();
+    let lock = LfrLock::new(socket(&drops));
+
+    for _ in 0..3 {
+        lock.store(socket(&drops));
+    }
+    lock.flush_retired();
+
+    // Every replaced value is dropped, the current one is kept
+    // 所有被替换的值都被 drop，当前值被保留
+    assert_eq!(drops.count(), 3);
+    assert_eq!(lock.version(), 3);
+}
+
+#[test]
+fn test_synchronize_waits_for_readers() {
+    let drops = Drops::new();
+    let lock = LfrLock::new(socket(&drops));
+    let factory = lock.factory();
+    let (pinned_tx, pinned_rx) = mpsc::channel();
+
+    let handle = thread::spawn(move || {
+        let lock = factory.create();
+        let _guard = lock.read();
+        pinned_tx.send(()).unwrap();
+        thread::sleep(Duration::from_millis(50));
+    });
+
+    pinned_rx.recv().unwrap();
+    lock.store(socket(&drops));
+    lock.synchronize();
+
+    // The old value's destructor has run before synchronize returned
+    // 在 synchronize 返回之前，旧值的析构函数已经运行
+    assert_eq!(drops.count(), 1);
+    handle.join().unwrap();
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn test_synchronize_timeout() {
+    let drops = Drops::new();
+    let lock = LfrLock::new(socket(&drops));
+    let reader = lock.clone();
+
+    let guard = reader.read();
+    lock.store(socket(&drops));
+    assert!(!lock.synchronize_timeout(Duration::from_millis(20)));
+    assert_eq!(drops.count(), 0);
+
+    drop(guard);
+    assert!(lock.synchronize_timeout(Duration::from_millis(20)));
+    assert_eq!(drops.count(), 1);
+}
+
+#[test]
+fn test_synchronize_without_retired_values() {
+    let drops = Drops::new();
+    let lock = LfrLock::new(socket(&drops));
+
+    lock.synchronize();
+    lock.flush_retired();
+    assert_eq!(drops.count(), 0);
+    assert_eq!(lock.version(), 0);
+}
+
+#[test]
+fn test_reclaim_hook_receives_retired_values() {
+    let lock = LfrLock::new(vec![0u8; 16]);
+    let pool = Arc::new(Mutex::new(Vec::new()));
+
+    let returned = pool.clone();
+    lock.set_reclaim_hook(move |version, buffer: Vec<u8>| {
+        returned.lock().unwrap().push((version, buffer.len()));
+    });
+
+    for len in 1..=3 {
+        lock.store(vec![0u8; len]);
+    }
+    lock.flush_retired();
+
+    // Each replaced buffer is handed back with its version, oldest first
+    // 每个被替换的缓冲区都连同其版本号一起交回，从旧到新
+    assert_eq!(*pool.lock().unwrap(), vec![(0, 16), (1, 1), (2, 2)]);
+
+    lock.clear_reclaim_hook();
+    lock.store(vec![]);
+    lock.flush_retired();
+    assert_eq!(pool.lock().unwrap().len(), 3);
+}
+
+/// Writer mutex that runs `ON_UNLOCK` right after an unlock, before the values reclaimed
+/// by that write are handed to the hook.
+///
+/// 在解锁之后、该次写入回收的值交给钩子之前，运行 `ON_UNLOCK` 的写入互斥锁。
+struct UnlockHookMutex(DefaultRawMutex);
+
+type OnUnlock = Box<dyn FnOnce() + Send>;
+
+static ON_UNLOCK: Mutex<Option<OnUnlock>> = Mutex::new(None);
+
+unsafe impl RawMutex for UnlockHookMutex {
+    #[allow(clippy::declare_interior_mutable_const)]
+    const INIT: Self = UnlockHookMutex(DefaultRawMutex::INIT);
+
+    type GuardMarker = GuardSend;
+
+    fn lock(&self) {
+        RawMutex::lock(&self.0);
+    }
+
+    fn try_lock(&self) -> bool {
+        RawMutex::try_lock(&self.0)
+    }
+
+    unsafe fn unlock(&self) {
+        unsafe { RawMutex::unlock(&self.0) };
+        let on_unlock = ON_UNLOCK.lock().unwrap().take();
+        if let Some(on_unlock) = on_unlock {
+            on_unlock();
+        }
+    }
+}
+
+#[test]
+fn test_clear_reclaim_hook_hands_over_pending_values() {
+    let drops = Drops::new();
+    let factory: LfrLockFactory<Socket, UnlockHookMutex> = LfrLockFactory::builder()
+        .raw_mutex()
+        .build_factory(socket(&drops));
+    let lock = factory.create();
+
+    let hooked = Arc::new(AtomicUsize::new(0));
+    let counted = hooked.clone();
+    factory.set_reclaim_hook(move |_, _| {
+        counted.fetch_add(1, Ordering::SeqCst);
+    });
+    for _ in 0..3 {
+        lock.store(socket(&drops));
+    }
+
+    // Clear the hook between the unlock of the reclaiming write and its hand-over
+    // 在进行回收的写入解锁之后、移交之前清除钩子
+    let clearing = factory.clone();
+    *ON_UNLOCK.lock().unwrap() = Some(Box::new(move || clearing.clear_reclaim_hook()));
+    lock.flush_retired();
+
+    // The old hook still gets every replaced value, and drops it
+    // 旧钩子仍会收到每个被替换的值，并将其 drop
+    assert_eq!(drops.count(), 3);
+    assert_eq!(hooked.load(Ordering::SeqCst), 3);
+}
+
+#[test]
+fn test_reclaim_hook_runs_outside_writer_mutex() {
+    let factory = LfrLockFactory::new(vec![0u8]);
+    let writable = Arc::new(AtomicUsize::new(0));
+
+    let lock = factory.create();
+    let hook_factory = factory.clone();
+    let hook_writable = writable.clone();
+    factory.set_reclaim_hook(move |_, _| {
+        // The write lock is free while the hook runs
+        // 钩子运行时写入锁是空闲的
+        if hook_factory.create().try_write().is_ok() {
+            hook_writable.fetch_add(1, Ordering::SeqCst);
+        }
+    });
+
+    lock.store(vec![1]);
+    lock.flush_retired();
+    assert_eq!(writable.load(Ordering::SeqCst), 1);
+}
+
+#[test]
+fn test_reclaim_hook_waits_for_readers() {
+    let lock = LfrLock::new(String::from("old"));
+    let reader = lock.clone();
+    let released = Arc::new(Mutex::new(Vec::new()));
+
+    let log = released.clone();
+    lock.set_reclaim_hook(move |version, value| log.lock().unwrap().push((version, value)));
+
+    let guard = reader.read();
+    lock.store(String::from("new"));
+    lock.flush_retired();
+    assert!(released.lock().unwrap().is_empty());
+
+    drop(guard);
+    lock.synchronize();
+    assert_eq!(*released.lock().unwrap(), vec![(0, String::from("old"))]);
+}
@@ -0,0 +1,150 @@
+#![cfg(feature = "std")]
+
+mod common;
+
+use common::{Drops, socket};
+use lfrlock::{LfrLock, OverflowPolicy};
+
+#[test]
+fn test_deferred_drop() {
+    let drops = Drops::new();
+    let lock = LfrLock::builder()
+        .deferred_drop(16, OverflowPolicy::DropInline)
+        .build(socket(&drops));
+
+    for _ in 0..3 {
+        lock.store(socket(&drops));
+    }
+    lock.flush_retired();
+
+    // Reclaimed values wait in the queue until drained
+    // 被回收的值在队列中等待，直到被清理
+    assert_eq!(drops.count(), 0);
+    assert_eq!(lock.drain_retired(), 3);
+    assert_eq!(drops.count(), 3);
+    assert_eq!(lock.drain_retired(), 0);
+}
+
+#[test]
+fn test_deferred_drop_overflow_inline() {
+    let drops = Drops::new();
+    let lock = LfrLock::builder()
+        .deferred_drop(1, OverflowPolicy::DropInline)
+        .build(socket(&drops));
+
+    for _ in 0..3 {
+        lock.store(socket(&drops));
+    }
+    lock.flush_retired();
+
+    // Only one value fits, the others are dropped right away
+    // 只能容纳一个值，其余的立即被 drop
+    assert_eq!(drops.count(), 2);
+    assert_eq!(lock.drain_retired(), 1);
+    assert_eq!(drops.count(), 3);
+}
+
+#[test]
+fn test_deferred_drop_on_lock_drop() {
+    let drops = Drops::new();
+    let lock = LfrLock::builder()
+        .deferred_drop(16, OverflowPolicy::Block)
+        .build(socket(&drops));
+
+    for _ in 0..3 {
+        lock.store(socket(&drops));
+    }
+    lock.flush_retired();
+    drop(lock);
+
+    // Queued values and the current one are dropped with the lock, without blocking
+    // 排队的值和当前值随锁一起被 drop，且不会阻塞
+    assert_eq!(drops.count(), 4);
+}
+
+#[test]
+fn test_deferred_drop_swap_retired() {
+    let drops = Drops::new();
+    let lock = LfrLock::builder()
+        .deferred_drop(16, OverflowPolicy::DropInline)
+        .build(socket(&drops));
+
+    // The handle owns the old value, it never waits in the queue
+    // 句柄拥有旧值，它永远不会在队列中等待
+    let retired = lock.swap_retired(socket(&drops));
+    drop(retired.into_inner());
+    assert_eq!(drops.count(), 1);
+
+    let retired = lock.swap_retired(socket(&drops));
+    lock.flush_retired();
+    let Ok(old) = retired.try_into_inner() else {
+        panic!("no reader can see the old value");
+    };
+    drop(old);
+    assert_eq!(drops.count(), 2);
+    assert_eq!(lock.drain_retired(), 0);
+
+    // Dropping the handle gives the value back to the queue
+    // drop 句柄会将值交还给队列
+    let retired = lock.swap_retired(socket(&drops));
+    lock.flush_retired();
+    drop(retired);
+    assert_eq!(drops.count(), 2);
+    assert_eq!(lock.drain_retired(), 1);
+    assert_eq!(drops.count(), 3);
+}
+
+#[test]
+fn test_deferred_drop_synchronize() {
+    let drops = Drops::new();
+    let lock = LfrLock::builder()
+        .deferred_drop(16, OverflowPolicy::DropInline)
+        .build(socket(&drops));
+
+    for _ in 0..3 {
+        lock.store(socket(&drops));
+    }
+
+    // Values retired before the call have been dropped once it returns
+    // 调用返回时，调用之前退休的值都已被 drop
+    lock.synchronize();
+    assert_eq!(drops.count(), 3);
+    assert_eq!(lock.drain_retired(), 0);
+}
+
+#[test]
+fn test_background_reclaimer() {
+    let drops = Drops::new();
+    let lock = LfrLock::builder()
+        .background_reclaimer(16, OverflowPolicy::DropInline)
+        .build(socket(&drops));
+
+    for _ in 0..3 {
+        lock.store(socket(&drops));
+    }
+    lock.flush_retired();
+    drops.wait_for(3);
+
+    assert!(drops.all_on("lfrlock-reclaimer"));
+}
+
+#[test]
+fn test_background_reclaimer_block() {
+    let drops = Drops::new();
+    let lock = LfrLock::builder()
+        .background_reclaimer(1, OverflowPolicy::Block)
+        .build(socket(&drops));
+
+    for _ in 0..200 {
+        lock.store(socket(&drops));
+    }
+    lock.flush_retired();
+    drops.wait_for(200);
+
+    // Writers waited for room instead of dropping anything themselves
+    // 写者等待空间，而不是自己 drop 任何值
+    assert!(drops.all_on("lfrlock-reclaimer"));
+
+    drop(lock);
+    drops.wait_for(201);
+}
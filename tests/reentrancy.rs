@@ -0,0 +1,151 @@
+#![cfg(feature = "reentrancy-check")]
+
+use lfrlock::{LfrLock, TryStoreError, TryUpdateError, TryWriteError};
+use std::future::Future;
+use std::panic::{AssertUnwindSafe, catch_unwind};
+use std::pin::pin;
+#[cfg(feature = "std")]
+use std::sync::mpsc;
+use std::task::{Context, Poll, Waker};
+#[cfg(any(feature = "std", not(feature = "critical-section")))]
+use std::thread;
+#[cfg(feature = "std")]
+use std::time::Duration;
+
+/// Tell test threads apart without `std`, where lfrlock has no thread identity of its own.
+///
+/// 没有 `std` 时区分测试线程，此时 lfrlock 自身没有线程标识。
+fn init() {
+    #[cfg(not(feature = "std"))]
+    lfrlock::set_context_id(|| {
+        thread_local! {
+            static IDENTITY: u8 = const { 0 };
+        }
+        IDENTITY.with(|identity| identity as *const u8 as usize)
+    });
+}
+
+#[test]
+#[should_panic(expected = "reentrant write")]
+fn test_write_in_write_panics() {
+    init();
+    let lock = LfrLock::new(0);
+    let _guard = lock.write();
+    let _again = lock.write();
+}
+
+#[test]
+fn test_write_through_other_handles_panics() {
+    init();
+    let lock = LfrLock::new(0);
+    let clone = lock.clone();
+    let created = lock.factory().create();
+
+    let guard = lock.write();
+    assert!(catch_unwind(AssertUnwindSafe(|| clone.store(1))).is_err());
+    assert!(catch_unwind(AssertUnwindSafe(|| created.update(|v| v + 1))).is_err());
+    drop(guard);
+
+    // The lock is usable again once the guard is gone
+    // 守卫消失后锁可以再次使用
+    clone.store(1);
+    created.update(|v| v + 1);
+    assert_eq!(*lock.read(), 2);
+}
+
+#[test]
+fn test_try_writes_report_reentrant() {
+    init();
+    let lock = LfrLock::new(0);
+    let guard = lock.write();
+
+    assert_eq!(lock.try_write().unwrap_err(), TryWriteError::Reentrant);
+    #[cfg(feature = "std")]
+    assert_eq!(
+        lock.write_timeout(Duration::from_secs(10)).unwrap_err(),
+        TryWriteError::Reentrant
+    );
+    match lock.try_store(1) {
+        Err(TryStoreError::Lock(TryWriteError::Reentrant, value)) => assert_eq!(value, 1),
+        other => panic!("unexpected {other:?}"),
+    }
+    assert_eq!(
+        lock.try_lock_update(|v| Ok::<_, ()>(v + 1)),
+        Err(TryUpdateError::Lock(TryWriteError::Reentrant))
+    );
+
+    drop(guard);
+    assert!(lock.try_write().is_ok());
+}
+
+#[cfg(any(feature = "std", not(feature = "critical-section")))]
+#[test]
+fn test_other_threads_are_not_reentrant() {
+    init();
+    let lock = LfrLock::new(0);
+    let _guard = lock.write();
+
+    let other = lock.clone();
+    thread::spawn(move || {
+        assert_eq!(other.try_write().unwrap_err(), TryWriteError::WouldBlock);
+        #[cfg(feature = "std")]
+        assert_eq!(
+            other.write_timeout(Duration::from_millis(1)).unwrap_err(),
+            TryWriteError::Timeout
+        );
+    })
+    .join()
+    .unwrap();
+}
+
+#[test]
+fn test_separate_locks_nest() {
+    init();
+    let a = LfrLock::new(0);
+    let b = LfrLock::new(0);
+
+    let mut guard_a = a.write();
+    let mut guard_b = b.write();
+    *guard_a = 1;
+    *guard_b = 2;
+    drop(guard_b);
+    drop(guard_a);
+    assert_eq!((*a.read(), *b.read()), (1, 2));
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn test_timed_acquisition_is_tracked() {
+    init();
+    let lock = LfrLock::new(0);
+    let other = lock.clone();
+    let (locked, wait_locked) = mpsc::channel();
+    let holder = thread::spawn(move || {
+        let _guard = other.write();
+        locked.send(()).unwrap();
+        thread::sleep(Duration::from_millis(10));
+    });
+    wait_locked.recv().unwrap();
+
+    // Acquired through the wait queue once the holder is done
+    // 持有者完成后通过等待队列获取
+    let _guard = lock.write_timeout(Duration::from_secs(10)).unwrap();
+    assert_eq!(lock.try_write().unwrap_err(), TryWriteError::Reentrant);
+    holder.join().unwrap();
+}
+
+#[test]
+fn test_async_acquisition_is_not_tracked() {
+    init();
+    let lock = LfrLock::new(0);
+    let mut future = pin!(lock.write_async());
+    let mut cx = Context::from_waker(Waker::noop());
+    let Poll::Ready(guard) = future.as_mut().poll(&mut cx) else {
+        panic!("uncontended lock must be ready");
+    };
+
+    // The task may resume on another thread, so this thread is not the owner
+    // 任务可能在其他线程上恢复执行，因此本线程不是持有者
+    assert_eq!(lock.try_write().unwrap_err(), TryWriteError::WouldBlock);
+    drop(guard);
+}
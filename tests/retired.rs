@@ -0,0 +1,100 @@
+use lfrlock::LfrLock;
+use std::sync::{Arc, Mutex, mpsc};
+use std::thread;
+use std::time::Duration;
+
+/// Resource that is deliberately not `Clone`.
+///
+/// 故意不实现 `Clone` 的资源。
+#[derive(Debug, PartialEq)]
+struct Pool {
+    id: u32,
+}
+
+#[test]
+fn test_swap_retired_without_clone() {
+    let lock = LfrLock::new(Pool { id: 1 });
+
+    let retired = lock.swap_retired(Pool { id: 2 });
+    assert_eq!(retired.id, 1);
+    assert_eq!(lock.read().id, 2);
+    assert_eq!(lock.version(), 1);
+
+    // No reader holds the old value, so it can be taken at once
+    // 没有读者持有旧值，因此可以立即取出
+    let old = retired.try_into_inner().unwrap();
+    assert_eq!(old, Pool { id: 1 });
+
+    // Reclaiming does not change the logical version or wake subscribers
+    // 回收不会改变逻辑版本号，也不会唤醒订阅者
+    assert_eq!(lock.version(), 1);
+    assert_eq!(lock.read().version(), 1);
+}
+
+#[test]
+fn test_try_into_inner_waits_for_readers() {
+    let lock = LfrLock::new(Pool { id: 1 });
+    let reader = lock.clone();
+    let subscriber = lock.subscribe();
+
+    let guard = reader.read();
+    let retired = lock.swap_retired(Pool { id: 2 });
+
+    // A reader still sees the old value
+    // 仍有读者看到旧值
+    let retired = retired.try_into_inner().unwrap_err();
+    assert_eq!(guard.id, 1);
+    assert_eq!(retired.id, 1);
+
+    drop(guard);
+    assert_eq!(retired.try_into_inner().unwrap().id, 1);
+    assert_eq!(subscriber.last_version(), 0);
+    assert_eq!(lock.version(), 1);
+}
+
+#[test]
+fn test_into_inner_blocks_until_readers_leave() {
+    let lock = LfrLock::new(Pool { id: 1 });
+    let factory = lock.factory();
+    let (pinned_tx, pinned_rx) = mpsc::channel();
+
+    let handle = thread::spawn(move || {
+        let lock = factory.create();
+        let guard = lock.read();
+        pinned_tx.send(()).unwrap();
+        thread::sleep(Duration::from_millis(50));
+        guard.id
+    });
+
+    pinned_rx.recv().unwrap();
+    let retired = lock.swap_retired(Pool { id: 2 });
+    let old = retired.into_inner();
+
+    assert_eq!(old, Pool { id: 1 });
+    assert_eq!(handle.join().unwrap(), 1);
+}
+
+#[test]
+fn test_dropped_handle_goes_to_reclaim_hook() {
+    let lock = LfrLock::new(Pool { id: 1 });
+    let log = Arc::new(Mutex::new(Vec::new()));
+    let logged = log.clone();
+    lock.set_reclaim_hook(move |version, pool: Pool| {
+        logged.lock().unwrap().push((version, pool.id))
+    });
+
+    // Reclaimed while the handle still shares it, the hook gets it once the handle is dropped
+    // 在句柄仍共享它时被回收，句柄被 drop 后钩子才会收到它
+    let retired = lock.swap_retired(Pool { id: 2 });
+    lock.flush_retired();
+    assert!(log.lock().unwrap().is_empty());
+    drop(retired);
+    assert_eq!(*log.lock().unwrap(), vec![(0, 1)]);
+
+    // Dropped first, the hook gets it once it is reclaimed
+    // 先被 drop，钩子在它被回收时收到它
+    drop(lock.swap_retired(Pool { id: 3 }));
+    assert_eq!(log.lock().unwrap().len(), 1);
+    lock.flush_retired();
+    assert_eq!(*log.lock().unwrap(), vec![(0, 1), (1, 2)]);
+}
@@ -0,0 +1,121 @@
+use lfrlock::{LfrLock, snapshot};
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::thread;
+
+#[test]
+fn test_snapshot_mixed_types() {
+    let a = LfrLock::new(1);
+    let b = LfrLock::new(String::from("b"));
+    let c = LfrLock::new(vec![3]);
+
+    let (single,) = snapshot((&a,));
+    assert_eq!(*single, 1);
+
+    let (a, b, c) = snapshot((&a, &b, &c));
+    assert_eq!((*a, b.as_str(), c.as_slice()), (1, "b", &[3][..]));
+}
+
+#[test]
+fn test_snapshot_of_sequential_stores() {
+    // `a`, `b` and `c` are stored in this order, so a consistent view never has a later lock
+    // ahead of an earlier one, nor a gap of more than one commit
+    // `a`、`b`、`c` 按此顺序存储，因此一致的视图中靠后的锁永远不会领先于靠前的锁，差距也不会超过一次提交
+    let a = LfrLock::new(0usize);
+    let b = LfrLock::new(0usize);
+    let c = LfrLock::new(0usize);
+    let done = Arc::new(AtomicBool::new(false));
+
+    let readers: Vec<_> = (0..2)
+        .map(|_| {
+            let (a, b, c, done) = (a.clone(), b.clone(), c.clone(), done.clone());
+            thread::spawn(move || {
+                while !done.load(Ordering::Relaxed) {
+                    let (a, b, c) = snapshot((&a, &b, &c));
+                    assert!(*a >= *b && *b >= *c, "a = {}, b = {}, c = {}", *a, *b, *c);
+                    assert!(*a - *c <= 1, "a = {}, b = {}, c = {}", *a, *b, *c);
+                }
+            })
+        })
+        .collect();
+
+    for i in 1..=5000 {
+        a.store(i);
+        b.store(i);
+        c.store(i);
+    }
+    done.store(true, Ordering::Relaxed);
+    for reader in readers {
+        reader.join().unwrap();
+    }
+}
+
+#[test]
+fn test_snapshot_never_sees_removed_backend() {
+    // Backends are added before a route points at them, and routes move away before the
+    // backend is removed
+    // 后端在路由指向它之前加入，路由在后端被移除之前移走
+    let routes = LfrLock::new(HashMap::from([("/api", 0usize)]));
+    let backends = LfrLock::new(vec![0usize]);
+    let done = Arc::new(AtomicBool::new(false));
+
+    let reader = {
+        let (routes, backends, done) = (routes.clone(), backends.clone(), done.clone());
+        thread::spawn(move || {
+            while !done.load(Ordering::Relaxed) {
+                let (routes, backends) = snapshot((&routes, &backends));
+                assert!(backends.contains(&routes["/api"]));
+            }
+        })
+    };
+
+    for next in 1..2000 {
+        backends.write().push(next);
+        routes.write().insert("/api", next);
+        backends.write().retain(|&backend| backend != next - 1);
+    }
+    done.store(true, Ordering::Relaxed);
+    reader.join().unwrap();
+}
+
+#[test]
+fn test_snapshot_with_transactions() {
+    // Transactions move units between three locks, snapshots always see the same total
+    // 事务在三个锁之间移动单位，快照总是看到相同的总量
+    let locks = [LfrLock::new(300i64), LfrLock::new(0i64), LfrLock::new(0i64)];
+    let done = Arc::new(AtomicBool::new(false));
+
+    let reader = {
+        let locks = locks.clone();
+        let done = done.clone();
+        thread::spawn(move || {
+            while !done.load(Ordering::Relaxed) {
+                let (a, b, c) = snapshot((&locks[0], &locks[1], &locks[2]));
+                assert_eq!(*a + *b + *c, 300);
+            }
+        })
+    };
+
+    let writers: Vec<_> = (0..3)
+        .map(|i| {
+            let locks = locks.clone();
+            thread::spawn(move || {
+                for _ in 0..1000 {
+                    lfrlock::transaction(|tx| {
+                        *tx.write(&locks[i]) -= 1;
+                        *tx.write(&locks[(i + 1) % 3]) += 1;
+                    });
+                }
+            })
+        })
+        .collect();
+    for writer in writers {
+        writer.join().unwrap();
+    }
+    done.store(true, Ordering::Relaxed);
+    reader.join().unwrap();
+
+    let (a, b, c) = snapshot((&locks[0], &locks[1], &locks[2]));
+    assert_eq!((*a, *b, *c), (300, 0, 0));
+}
@@ -0,0 +1,67 @@
+use lfrlock::{LfrLock, LfrLockFactory, Strategy};
+use std::thread;
+
+#[test]
+fn test_default_strategy() {
+    let lock = LfrLock::new(0);
+    let expected = if cfg!(feature = "read-preferred") {
+        Strategy::ReadPreferred
+    } else {
+        Strategy::WritePreferred
+    };
+    assert_eq!(Strategy::default(), expected);
+    assert_eq!(lock.strategy(), expected);
+}
+
+#[test]
+fn test_mixed_strategies() {
+    // Both strategies live side by side in the same binary
+    // 两种策略在同一个二进制中并存
+    let config = LfrLock::builder()
+        .strategy(Strategy::ReadPreferred)
+        .build(String::from("v0"));
+    let sessions = LfrLock::builder()
+        .strategy(Strategy::WritePreferred)
+        .build(Vec::<u32>::new());
+    assert_eq!(config.strategy(), Strategy::ReadPreferred);
+    assert_eq!(sessions.strategy(), Strategy::WritePreferred);
+
+    let config_factory = config.factory();
+    let sessions_factory = sessions.factory();
+    let handles: Vec<_> = (0..4)
+        .map(|i| {
+            let config_factory = config_factory.clone();
+            let sessions_factory = sessions_factory.clone();
+            thread::spawn(move || {
+                let config = config_factory.create();
+                let sessions = sessions_factory.create();
+                assert_eq!(config.strategy(), Strategy::ReadPreferred);
+                assert_eq!(sessions.strategy(), Strategy::WritePreferred);
+                for j in 0..100 {
+                    sessions.write().push(i * 100 + j);
+                    assert!(config.read().starts_with('v'));
+                }
+            })
+        })
+        .collect();
+
+    config.store(String::from("v1"));
+    for handle in handles {
+        handle.join().unwrap();
+    }
+
+    assert_eq!(sessions.read().len(), 400);
+    assert_eq!(*config.read(), "v1");
+}
+
+#[test]
+fn test_factory_builder() {
+    let factory = LfrLockFactory::builder()
+        .strategy(Strategy::ReadPreferred)
+        .build_factory(1);
+    let lock = factory.create();
+    assert_eq!(lock.strategy(), Strategy::ReadPreferred);
+
+    lock.store(2);
+    assert_eq!(*factory.create().read(), 2);
+}
@@ -0,0 +1,153 @@
+mod common;
+
+use common::block_on;
+use lfrlock::{LfrLock, Subscriber};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::task::{Context, Wake, Waker};
+use std::thread;
+
+#[derive(Debug, Clone, PartialEq)]
+struct Data {
+    value: i32,
+}
+
+/// Waker that counts how often it was woken.
+///
+/// 记录被唤醒次数的 waker。
+struct CountingWaker(AtomicUsize);
+
+impl Wake for CountingWaker {
+    fn wake(self: Arc<Self>) {
+        self.0.fetch_add(1, Ordering::SeqCst);
+    }
+}
+
+#[test]
+fn test_subscriber_has_changed() {
+    let lock = LfrLock::new(Data { value: 0 });
+    let mut subscriber = lock.subscribe();
+
+    // Current value counts as seen
+    // 当前值视为已看到
+    assert!(!subscriber.has_changed());
+    assert_eq!(subscriber.last_version(), 0);
+
+    lock.store(Data { value: 1 });
+    assert!(subscriber.has_changed());
+    assert_eq!(subscriber.borrow().value, 1);
+    assert!(subscriber.has_changed());
+
+    assert_eq!(subscriber.borrow_and_update().value, 1);
+    assert!(!subscriber.has_changed());
+    assert_eq!(subscriber.last_version(), 1);
+}
+
+#[test]
+fn test_subscriber_latest_value_wins() {
+    let lock = LfrLock::new(Data { value: 0 });
+    let mut subscriber = lock.subscribe();
+
+    lock.store(Data { value: 1 });
+    lock.store(Data { value: 2 });
+    lock.store(Data { value: 3 });
+
+    // Several commits are reported as one change with the latest value
+    // 多次提交被报告为一次变更，并携带最新值
+    let value = block_on(async { subscriber.changed().await.value });
+    assert_eq!(value, 3);
+    assert!(!subscriber.has_changed());
+}
+
+#[test]
+fn test_every_commit_path_wakes_subscriber() {
+    let lock = LfrLock::new(Data { value: 0 });
+    let mut subscriber = lock.subscribe();
+    let counter = Arc::new(CountingWaker(AtomicUsize::new(0)));
+    let waker = Waker::from(counter.clone());
+    let mut cx = Context::from_waker(&waker);
+
+    // Consume the change and register again
+    // 消费变更并重新注册
+    fn consume_change(subscriber: &mut Subscriber<Data>, cx: &mut Context<'_>) {
+        assert!(subscriber.poll_change(cx).is_ready());
+        subscriber.borrow_and_update();
+        assert!(subscriber.poll_change(cx).is_pending());
+    }
+
+    assert!(subscriber.poll_change(&mut cx).is_pending());
+    lock.store(Data { value: 1 });
+    assert_eq!(counter.0.load(Ordering::SeqCst), 1);
+    consume_change(&mut subscriber, &mut cx);
+
+    lock.update(|d| Data { value: d.value + 1 });
+    assert_eq!(counter.0.load(Ordering::SeqCst), 2);
+    consume_change(&mut subscriber, &mut cx);
+
+    let _ = lock.swap(Data { value: 3 });
+    assert_eq!(counter.0.load(Ordering::SeqCst), 3);
+    consume_change(&mut subscriber, &mut cx);
+
+    lock.write().value = 4;
+    assert_eq!(counter.0.load(Ordering::SeqCst), 4);
+    consume_change(&mut subscriber, &mut cx);
+
+    assert_eq!(subscriber.last_version(), 4);
+}
+
+#[test]
+fn test_subscriber_across_threads() {
+    let lock = LfrLock::new(Data { value: 0 });
+    let mut subscriber = lock.subscribe();
+
+    let handle = thread::spawn(move || {
+        block_on(async {
+            let mut seen = vec![];
+            loop {
+                let value = subscriber.changed().await.value;
+                seen.push(value);
+                if value == 100 {
+                    return seen;
+                }
+            }
+        })
+    });
+
+    for i in 1..=100 {
+        lock.store(Data { value: i });
+    }
+
+    // Values are observed in commit order, possibly skipping some
+    // 值按提交顺序被观察到，可能会跳过一些
+    let seen = handle.join().unwrap();
+    assert_eq!(seen.last(), Some(&100));
+    assert!(seen.windows(2).all(|w| w[0] < w[1]));
+}
+
+#[cfg(feature = "stream")]
+#[test]
+fn test_subscriber_stream() {
+    use futures_core::Stream;
+    use std::pin::pin;
+    use std::task::Poll;
+
+    let lock = LfrLock::new(Data { value: 0 });
+    let mut subscriber = pin!(lock.subscribe());
+    let counter = Arc::new(CountingWaker(AtomicUsize::new(0)));
+    let waker = Waker::from(counter.clone());
+    let mut cx = Context::from_waker(&waker);
+
+    assert!(subscriber.as_mut().poll_next(&mut cx).is_pending());
+
+    lock.store(Data { value: 1 });
+    lock.store(Data { value: 2 });
+    assert_eq!(counter.0.load(Ordering::SeqCst), 1);
+
+    // Stream yields a snapshot of the latest value
+    // 流产出最新值的快照
+    assert_eq!(
+        subscriber.as_mut().poll_next(&mut cx),
+        Poll::Ready(Some(Data { value: 2 }))
+    );
+    assert!(subscriber.as_mut().poll_next(&mut cx).is_pending());
+}
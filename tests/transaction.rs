@@ -0,0 +1,148 @@
+use lfrlock::{LfrLock, snapshot, transaction};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::thread;
+
+#[test]
+fn test_transaction_writes_both() {
+    let a = LfrLock::new(1);
+    let b = LfrLock::new(String::from("one"));
+
+    let sum = transaction(|tx| {
+        *tx.write(&a) += 1;
+        tx.store(&b, String::from("two"));
+        *tx.read(&a) + 10
+    });
+    assert_eq!(sum, 12);
+    assert_eq!((*a.read(), b.read().as_str()), (2, "two"));
+    assert_eq!((a.version(), b.version()), (1, 1));
+}
+
+#[test]
+fn test_transaction_reads_own_writes() {
+    let a = LfrLock::new(vec![1]);
+    transaction(|tx| {
+        assert_eq!(tx.read(&a), &vec![1]);
+        tx.write(&a).push(2);
+        assert_eq!(tx.read(&a), &vec![1, 2]);
+        tx.write(&a).push(3);
+    });
+    assert_eq!(*a.read(), vec![1, 2, 3]);
+}
+
+#[test]
+fn test_read_only_transaction_commits_nothing() {
+    let a = LfrLock::new(1);
+    let b = LfrLock::new(2);
+    let sum = transaction(|tx| {
+        let a = *tx.read(&a);
+        a + tx.read(&b)
+    });
+    assert_eq!(sum, 3);
+    assert_eq!((a.version(), b.version()), (0, 0));
+}
+
+#[test]
+fn test_transaction_retries_after_conflict() {
+    let a = LfrLock::new(0);
+    let b = LfrLock::new(0);
+    let runs = AtomicUsize::new(0);
+
+    transaction(|tx| {
+        let value = *tx.read(&a);
+        if runs.fetch_add(1, Ordering::Relaxed) == 0 {
+            // A commit between the read and the commit forces a retry
+            // 在读取和提交之间发生的提交会强制重试
+            let other = a.clone();
+            thread::spawn(move || other.store(5)).join().unwrap();
+        }
+        tx.store(&b, value * 2);
+    });
+
+    assert_eq!(runs.load(Ordering::Relaxed), 2);
+    assert_eq!(*b.read(), 10);
+}
+
+#[test]
+fn test_transaction_skip_equal() {
+    let a = LfrLock::builder().skip_equal().build(1);
+    let b = LfrLock::new(1);
+    transaction(|tx| {
+        tx.store(&a, 1);
+        tx.store(&b, 2);
+    });
+    assert_eq!((a.version(), b.version()), (0, 1));
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn test_transaction_wakes_waiters() {
+    let a = LfrLock::new(0);
+    let b = LfrLock::new(0);
+
+    let waiter = {
+        let b = b.clone();
+        thread::spawn(move || *b.wait_for_change(0))
+    };
+    transaction(|tx| {
+        tx.store(&a, 1);
+        tx.store(&b, 2);
+    });
+    assert_eq!(waiter.join().unwrap(), 2);
+}
+
+#[test]
+fn test_transfers_keep_total() {
+    // Transfers between two accounts never change their sum, as seen by snapshots
+    // 两个账户之间的转账永远不会改变其总和，快照所见亦如此
+    let from = LfrLock::new(1000i64);
+    let to = LfrLock::new(0i64);
+    let done = Arc::new(AtomicBool::new(false));
+
+    let readers: Vec<_> = (0..2)
+        .map(|_| {
+            let (from, to, done) = (from.clone(), to.clone(), done.clone());
+            thread::spawn(move || {
+                let mut seen = 0;
+                while !done.load(Ordering::Relaxed) {
+                    let (from, to) = snapshot((&from, &to));
+                    assert_eq!(*from + *to, 1000);
+                    seen += 1;
+                }
+                seen
+            })
+        })
+        .collect();
+
+    let writers: Vec<_> = (0..4)
+        .map(|i| {
+            let (from, to) = (from.clone(), to.clone());
+            thread::spawn(move || {
+                for _ in 0..2000 {
+                    // Alternate the order in which the locks are first touched
+                    // 交替首次访问锁的顺序
+                    transaction(|tx| {
+                        if i % 2 == 0 {
+                            *tx.write(&from) -= 1;
+                            *tx.write(&to) += 1;
+                        } else {
+                            *tx.write(&to) += 1;
+                            *tx.write(&from) -= 1;
+                        }
+                    });
+                }
+            })
+        })
+        .collect();
+
+    for writer in writers {
+        writer.join().unwrap();
+    }
+    done.store(true, Ordering::Relaxed);
+    for reader in readers {
+        assert!(reader.join().unwrap() > 0);
+    }
+
+    let (from, to) = snapshot((&from, &to));
+    assert_eq!((*from, *to), (-7000, 8000));
+}
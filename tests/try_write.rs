@@ -0,0 +1,157 @@
+use lfrlock::{LfrLock, TryStoreError, TryUpdateError, TryWriteError};
+use std::panic::{AssertUnwindSafe, catch_unwind};
+#[cfg(any(feature = "std", not(feature = "critical-section")))]
+use std::sync::mpsc;
+#[cfg(any(feature = "std", not(feature = "critical-section")))]
+use std::thread::{self, JoinHandle};
+#[cfg(feature = "std")]
+use std::time::{Duration, Instant};
+
+/// Hold the write lock on another thread until the returned sender is dropped.
+///
+/// Not available with the `critical-section` default mutex, which holds the process-wide
+/// critical section and would block the current thread as well.
+///
+/// 在另一个线程上持有写入锁，直到返回的 sender 被 drop。
+///
+/// 使用 `critical-section` 的默认互斥锁时不可用，它持有进程范围的临界区，也会阻塞当前线程。
+#[cfg(any(feature = "std", not(feature = "critical-section")))]
+fn hold_write(lock: &LfrLock<i32>) -> (mpsc::Sender<()>, JoinHandle<()>) {
+    let lock = lock.clone();
+    let (locked, wait_locked) = mpsc::channel();
+    let (release, wait_release) = mpsc::channel::<()>();
+    let handle = thread::spawn(move || {
+        let _guard = lock.write();
+        locked.send(()).unwrap();
+        let _ = wait_release.recv();
+    });
+    wait_locked.recv().unwrap();
+    (release, handle)
+}
+
+#[cfg(any(feature = "std", not(feature = "critical-section")))]
+#[test]
+fn test_try_write_would_block() {
+    let lock = LfrLock::new(0);
+    let (release, handle) = hold_write(&lock);
+
+    assert_eq!(lock.try_write().unwrap_err(), TryWriteError::WouldBlock);
+
+    drop(release);
+    handle.join().unwrap();
+    *lock.try_write().unwrap() = 1;
+    assert_eq!(*lock.read(), 1);
+}
+
+#[cfg(any(feature = "std", not(feature = "critical-section")))]
+#[test]
+fn test_try_store_and_update_would_block() {
+    let lock = LfrLock::new(0);
+    let (release, handle) = hold_write(&lock);
+
+    match lock.try_store(1) {
+        Err(TryStoreError::Lock(TryWriteError::WouldBlock, value)) => assert_eq!(value, 1),
+        other => panic!("unexpected {other:?}"),
+    }
+    let result = lock.try_lock_update(|_| -> Result<i32, ()> { panic!("must not run") });
+    assert_eq!(result, Err(TryUpdateError::Lock(TryWriteError::WouldBlock)));
+
+    drop(release);
+    handle.join().unwrap();
+    assert_eq!(lock.try_store(1).unwrap(), 1);
+    assert_eq!(lock.try_lock_update(|v| Ok::<_, ()>(v + 1)), Ok(2));
+    assert_eq!(*lock.read(), 2);
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn test_write_timeout_expires() {
+    let lock = LfrLock::new(0);
+    let (release, handle) = hold_write(&lock);
+
+    let start = Instant::now();
+    let err = lock.write_timeout(Duration::from_millis(20)).unwrap_err();
+    assert_eq!(err, TryWriteError::Timeout);
+    assert!(start.elapsed() >= Duration::from_millis(20));
+
+    drop(release);
+    handle.join().unwrap();
+    assert!(lock.write_timeout(Duration::ZERO).is_ok());
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn test_write_timeout_woken_by_unlock() {
+    let lock = LfrLock::new(0);
+    let (release, handle) = hold_write(&lock);
+
+    let releaser = thread::spawn(move || {
+        thread::sleep(Duration::from_millis(10));
+        drop(release);
+    });
+
+    // The unlock wakes the waiting thread long before the timeout
+    // 解锁会在超时之前很久唤醒等待的线程
+    let start = Instant::now();
+    *lock.write_timeout(Duration::from_secs(10)).unwrap() = 1;
+    assert!(start.elapsed() < Duration::from_secs(5));
+
+    releaser.join().unwrap();
+    handle.join().unwrap();
+    assert_eq!(*lock.read(), 1);
+}
+
+// Without reentrancy detection, a lock held by this thread looks like any other
+// 没有重入检测时，本线程持有的锁与其他锁看起来没有区别
+#[cfg(not(feature = "reentrancy-check"))]
+#[test]
+fn test_reentrant_try_write_not_detected() {
+    let lock = LfrLock::new(0);
+    let guard = lock.write();
+    assert_eq!(lock.try_write().unwrap_err(), TryWriteError::WouldBlock);
+    drop(guard);
+    assert!(lock.try_write().is_ok());
+}
+
+#[test]
+fn test_close() {
+    let lock = LfrLock::new(0);
+    let other = lock.factory().create();
+    lock.store(1);
+    lock.close();
+    assert!(other.is_closed());
+
+    assert_eq!(other.try_write().unwrap_err(), TryWriteError::Closed);
+    #[cfg(feature = "std")]
+    assert_eq!(
+        other.write_timeout(Duration::from_millis(1)).unwrap_err(),
+        TryWriteError::Closed
+    );
+    match other.try_store(2) {
+        Err(TryStoreError::Lock(TryWriteError::Closed, value)) => assert_eq!(value, 2),
+        other => panic!("unexpected {other:?}"),
+    }
+    assert_eq!(
+        other.try_lock_update(|v| Ok::<_, ()>(v + 1)),
+        Err(TryUpdateError::Lock(TryWriteError::Closed))
+    );
+
+    // Reads keep working
+    // 读取照常进行
+    assert_eq!(*other.read(), 1);
+    assert_eq!(other.version(), 1);
+}
+
+#[test]
+fn test_blocking_write_after_close_panics() {
+    let lock = LfrLock::builder().poisoning(true).build(0);
+    lock.close();
+
+    let result = catch_unwind(AssertUnwindSafe(|| lock.store(1)));
+    assert!(result.is_err());
+    assert_eq!(*lock.read(), 0);
+
+    // The guard was released before panicking
+    // 守卫在 panic 之前已被释放
+    assert!(!lock.is_poisoned());
+}
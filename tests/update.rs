@@ -0,0 +1,92 @@
+use lfrlock::LfrLock;
+use std::thread;
+
+#[derive(Debug, Clone, PartialEq)]
+struct Config {
+    limit: i32,
+}
+
+#[test]
+fn test_try_update() {
+    let lock = LfrLock::new(Config { limit: 10 });
+
+    assert_eq!(
+        lock.try_update(|c| Ok::<_, ()>(Config { limit: c.limit + 1 })),
+        Ok(1)
+    );
+    assert_eq!(lock.read().limit, 11);
+
+    // Validation failure leaves the value untouched
+    // 校验失败时值保持不变
+    let result = lock.try_update(|c| {
+        if c.limit > 5 {
+            Err("limit too high")
+        } else {
+            Ok(Config { limit: c.limit * 2 })
+        }
+    });
+    assert_eq!(result, Err("limit too high"));
+    assert_eq!(lock.read().limit, 11);
+    assert_eq!(lock.version(), 1);
+}
+
+#[test]
+fn test_update_with() {
+    let lock = LfrLock::new(Config { limit: 10 });
+
+    let old = lock.update_with(|c| (Config { limit: c.limit * 2 }, c.limit));
+    assert_eq!(old, 10);
+    assert_eq!(lock.read().limit, 20);
+    assert_eq!(lock.version(), 1);
+}
+
+#[test]
+fn test_write_with() {
+    let lock = LfrLock::new(Config { limit: 10 });
+
+    let doubled = lock.write_with(|c| {
+        c.limit *= 2;
+        c.limit
+    });
+    assert_eq!(doubled, 20);
+    assert_eq!(lock.read().limit, 20);
+    assert_eq!(lock.version(), 1);
+}
+
+#[test]
+fn test_update_if() {
+    let lock = LfrLock::new(Config { limit: 10 });
+
+    assert_eq!(
+        lock.update_if(|c| c.limit < 20, |c| Config { limit: c.limit + 5 }),
+        Some(1)
+    );
+    assert_eq!(lock.update_if(|c| c.limit > 20, |_| unreachable!()), None);
+    assert_eq!(lock.read().limit, 15);
+    assert_eq!(lock.version(), 1);
+}
+
+#[test]
+fn test_update_if_serialized() {
+    let lock = LfrLock::new(Config { limit: 0 });
+    let factory = lock.factory();
+
+    // The check and the update are atomic, so the limit is never exceeded
+    // 检查与更新是原子的，因此永远不会超过上限
+    let mut handles = vec![];
+    for _ in 0..4 {
+        let factory = factory.clone();
+        handles.push(thread::spawn(move || {
+            let lock = factory.create();
+            for _ in 0..100 {
+                lock.update_if(|c| c.limit < 150, |c| Config { limit: c.limit + 1 });
+            }
+        }));
+    }
+
+    for handle in handles {
+        handle.join().unwrap();
+    }
+
+    assert_eq!(lock.read().limit, 150);
+}
@@ -0,0 +1,134 @@
+use lfrlock::LfrLock;
+use std::thread;
+
+#[derive(Debug, Clone, PartialEq)]
+struct Data {
+    value: i32,
+}
+
+#[test]
+fn test_version_bumps_on_every_commit() {
+    let lock = LfrLock::new(Data { value: 0 });
+    assert_eq!(lock.version(), 0);
+
+    // Every commit path bumps the version by one
+    // 每条提交路径都会使版本号加一
+    lock.store(Data { value: 1 });
+    assert_eq!(lock.version(), 1);
+
+    lock.update(|d| Data { value: d.value + 1 });
+    assert_eq!(lock.version(), 2);
+
+    let _ = lock.swap(Data { value: 3 });
+    assert_eq!(lock.version(), 3);
+
+    {
+        let mut guard = lock.write();
+        guard.value = 4;
+    }
+    assert_eq!(lock.version(), 4);
+
+    let guard = lock.read();
+    assert_eq!(guard.value, 4);
+    assert_eq!(guard.version(), 4);
+}
+
+#[test]
+fn test_versions_shared_between_handles() {
+    let lock = LfrLock::new(Data { value: 0 });
+    let lock2 = lock.clone();
+    let lock3 = lock.factory().create();
+
+    lock2.store(Data { value: 1 });
+    lock3.store(Data { value: 2 });
+
+    assert_eq!(lock.version(), 2);
+    assert_eq!(lock2.read().version(), 2);
+    assert_eq!(lock3.read().version(), 2);
+}
+
+#[test]
+fn test_is_stale() {
+    let lock = LfrLock::new(Data { value: 0 });
+
+    let guard = lock.read();
+    assert!(!guard.is_stale());
+
+    // Guard keeps the old value but reports that it is stale
+    // 守卫保留旧值，但报告其已过期
+    lock.store(Data { value: 1 });
+    assert!(guard.is_stale());
+    assert_eq!(guard.value, 0);
+    assert_eq!(guard.version(), 0);
+
+    let fresh = lock.read();
+    assert!(!fresh.is_stale());
+    assert_eq!(fresh.value, 1);
+}
+
+#[test]
+fn test_compare_and_store() {
+    let lock = LfrLock::new(Data { value: 0 });
+    let version = lock.version();
+
+    assert_eq!(lock.compare_and_store(version, Data { value: 1 }), Ok(1));
+    assert_eq!(lock.read().value, 1);
+
+    // Stale version is rejected and the value is handed back
+    // 过期的版本号被拒绝，值被原样返回
+    let rejected = lock.compare_and_store(version, Data { value: 2 });
+    assert_eq!(rejected, Err(Data { value: 2 }));
+    assert_eq!(lock.read().value, 1);
+    assert_eq!(lock.version(), 1);
+}
+
+#[test]
+fn test_compare_and_update() {
+    let lock = LfrLock::new(Data { value: 10 });
+
+    assert_eq!(
+        lock.compare_and_update(0, |d| Data { value: d.value + 1 }),
+        Ok(1)
+    );
+    assert_eq!(lock.read().value, 11);
+
+    // Closure is not called on mismatch
+    // 不匹配时不会调用闭包
+    let result = lock.compare_and_update(0, |_| unreachable!());
+    assert_eq!(result, Err(1));
+    assert_eq!(lock.read().value, 11);
+}
+
+#[test]
+fn test_optimistic_concurrency() {
+    let lock = LfrLock::new(Data { value: 0 });
+    let factory = lock.factory();
+
+    let mut handles = vec![];
+    for _ in 0..4 {
+        let factory = factory.clone();
+        handles.push(thread::spawn(move || {
+            let lock = factory.create();
+            for _ in 0..50 {
+                // Retry until no other commit happened in between
+                // 重试直到期间没有其他提交
+                loop {
+                    let guard = lock.read();
+                    let next = Data {
+                        value: guard.value + 1,
+                    };
+                    if lock.compare_and_store(guard.version(), next).is_ok() {
+                        break;
+                    }
+                }
+            }
+        }));
+    }
+
+    for handle in handles {
+        handle.join().unwrap();
+    }
+
+    assert_eq!(lock.read().value, 200);
+    assert_eq!(lock.version(), 200);
+}
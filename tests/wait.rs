@@ -0,0 +1,105 @@
+#![cfg(feature = "std")]
+
+use lfrlock::{LfrLock, wait_any, wait_any_timeout};
+use std::thread;
+use std::time::{Duration, Instant};
+
+#[derive(Debug, Clone, PartialEq)]
+struct Data {
+    value: i32,
+}
+
+#[test]
+fn test_wait_until_already_satisfied() {
+    let lock = LfrLock::new(Data { value: 5 });
+
+    // Returns at once without parking
+    // 立即返回而不挂起
+    let guard = lock.wait_until(|d| d.value == 5);
+    assert_eq!(guard.value, 5);
+    assert_eq!(guard.version(), 0);
+}
+
+#[test]
+fn test_wait_until_across_threads() {
+    let lock = LfrLock::new(Data { value: 0 });
+    let factory = lock.factory();
+
+    let handle = thread::spawn(move || {
+        let lock = factory.create();
+        lock.wait_until(|d| d.value >= 3).cloned()
+    });
+
+    // Intermediate values do not satisfy the predicate
+    // 中间值不满足谓词
+    for value in 1..=3 {
+        thread::sleep(Duration::from_millis(10));
+        lock.store(Data { value });
+    }
+
+    assert_eq!(handle.join().unwrap(), Data { value: 3 });
+}
+
+#[test]
+fn test_wait_until_timeout() {
+    let lock = LfrLock::new(Data { value: 0 });
+
+    let start = Instant::now();
+    assert!(
+        lock.wait_until_timeout(|d| d.value == 1, Duration::from_millis(50))
+            .is_none()
+    );
+    assert!(start.elapsed() >= Duration::from_millis(50));
+
+    lock.store(Data { value: 1 });
+    let guard = lock.wait_until_timeout(|d| d.value == 1, Duration::from_millis(50));
+    assert_eq!(guard.unwrap().value, 1);
+}
+
+#[test]
+fn test_wait_for_change() {
+    let lock = LfrLock::new(Data { value: 0 });
+    let factory = lock.factory();
+    let since = lock.version();
+
+    let handle = thread::spawn(move || {
+        let lock = factory.create();
+        lock.wait_for_change(since).version()
+    });
+
+    thread::sleep(Duration::from_millis(20));
+    lock.update(|d| Data { value: d.value + 1 });
+
+    assert_eq!(handle.join().unwrap(), 1);
+
+    // A version that is already outdated returns immediately
+    // 已过期的版本号立即返回
+    assert_eq!(lock.wait_for_change(since).value, 1);
+}
+
+#[test]
+fn test_wait_any() {
+    let flags = LfrLock::new(false);
+    let shards = LfrLock::new(Data { value: 0 });
+    let flags_factory = flags.factory();
+    let shards_factory = shards.factory();
+    let (flags_version, shards_version) = (flags.version(), shards.version());
+
+    let handle = thread::spawn(move || {
+        let flags = flags_factory.create();
+        let shards = shards_factory.create();
+        wait_any(&[(&flags, flags_version), (&shards, shards_version)])
+    });
+
+    thread::sleep(Duration::from_millis(20));
+    shards.store(Data { value: 1 });
+    assert_eq!(handle.join().unwrap(), 1);
+
+    assert_eq!(
+        wait_any_timeout(
+            &[(&flags, flags.version()), (&shards, shards.version())],
+            Duration::from_millis(20)
+        ),
+        None
+    );
+}
@@ -0,0 +1,159 @@
+use lfrlock::LfrLock;
+#[cfg(feature = "std")]
+use std::panic::{AssertUnwindSafe, catch_unwind};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+#[derive(Debug, Clone, PartialEq)]
+struct Data {
+    a: i32,
+    b: i32,
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn test_panic_discards_draft() {
+    let lock = LfrLock::new(Data { a: 0, b: 0 });
+
+    let result = catch_unwind(AssertUnwindSafe(|| {
+        let mut guard = lock.write();
+        guard.a = 1;
+        panic!("mutation failed halfway");
+    }));
+    assert!(result.is_err());
+
+    // Half-modified value is never published
+    // 修改了一半的值永远不会被发布
+    assert_eq!(*lock.read(), Data { a: 0, b: 0 });
+    assert_eq!(lock.version(), 0);
+
+    // Lock is usable again after the panic
+    // panic 之后锁可以再次使用
+    lock.write().b = 2;
+    assert_eq!(*lock.read(), Data { a: 0, b: 2 });
+}
+
+#[test]
+fn test_commit() {
+    let lock = LfrLock::new(Data { a: 0, b: 0 });
+
+    let mut guard = lock.write();
+    guard.a = 1;
+    assert_eq!(guard.commit(), 1);
+
+    assert_eq!(lock.read().a, 1);
+    assert_eq!(lock.version(), 1);
+}
+
+#[test]
+fn test_abort() {
+    let lock = LfrLock::new(Data { a: 0, b: 0 });
+
+    // Roll back on validation failure without panicking
+    // 校验失败时回滚而无需 panic
+    let mut guard = lock.write();
+    guard.a = -1;
+    if guard.a < 0 {
+        guard.abort();
+    }
+
+    assert_eq!(lock.read().a, 0);
+    assert_eq!(lock.version(), 0);
+    assert!(lock.try_write().is_ok());
+}
+
+#[test]
+fn test_write_explicit() {
+    let lock = LfrLock::new(Data { a: 0, b: 0 });
+
+    // Dropping without commit discards the draft
+    // 未提交就 drop 会丢弃草稿
+    lock.write_explicit().a = 1;
+    assert_eq!(lock.read().a, 0);
+    assert_eq!(lock.version(), 0);
+
+    let mut guard = lock.write_explicit();
+    guard.a = 2;
+    guard.commit();
+    assert_eq!(lock.read().a, 2);
+    assert_eq!(lock.version(), 1);
+}
+
+/// Value that counts how often it was cloned.
+///
+/// 记录被克隆次数的值。
+#[derive(Debug)]
+struct Counted {
+    value: i32,
+    clones: Arc<AtomicUsize>,
+}
+
+impl Clone for Counted {
+    fn clone(&self) -> Self {
+        self.clones.fetch_add(1, Ordering::SeqCst);
+        Counted {
+            value: self.value,
+            clones: self.clones.clone(),
+        }
+    }
+}
+
+#[test]
+fn test_lazy_clone() {
+    let clones = Arc::new(AtomicUsize::new(0));
+    let lock = LfrLock::new(Counted {
+        value: 0,
+        clones: clones.clone(),
+    });
+
+    // Inspecting through the guard neither clones nor commits
+    // 通过守卫查看既不会克隆也不会提交
+    {
+        let guard = lock.write();
+        assert_eq!(guard.value, 0);
+    }
+    assert_eq!(clones.load(Ordering::SeqCst), 0);
+    assert_eq!(lock.version(), 0);
+    assert_eq!(lock.write().commit(), 0);
+
+    // The first mutable access clones once
+    // 第一次可变访问克隆一次
+    {
+        let mut guard = lock.write();
+        guard.value += 1;
+        guard.value += 1;
+        assert_eq!(guard.value, 2);
+    }
+    assert_eq!(clones.load(Ordering::SeqCst), 1);
+    assert_eq!(lock.read().value, 2);
+    assert_eq!(lock.version(), 1);
+}
+
+#[test]
+fn test_skip_equal() {
+    let lock = LfrLock::builder().skip_equal().build(Data { a: 0, b: 0 });
+    let mut subscriber = lock.subscribe();
+
+    // Mutations that end at the old value are not committed
+    // 最终等于旧值的修改不会被提交
+    {
+        let mut guard = lock.write();
+        guard.a = 1;
+        guard.a = 0;
+    }
+    lock.store(Data { a: 0, b: 0 });
+    lock.update(|d| d.clone());
+    assert_eq!(lock.version(), 0);
+    assert!(!subscriber.has_changed());
+
+    lock.store(Data { a: 1, b: 0 });
+    assert_eq!(lock.version(), 1);
+    assert!(subscriber.has_changed());
+    assert_eq!(subscriber.borrow_and_update().a, 1);
+
+    // Without the option every commit bumps the version
+    // 不启用该选项时，每次提交都会递增版本号
+    let plain = LfrLock::new(Data { a: 0, b: 0 });
+    plain.store(Data { a: 0, b: 0 });
+    assert_eq!(plain.version(), 1);
+}